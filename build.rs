@@ -1,6 +1,35 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Embeds the commit and build time for `/api/version` as `GIT_SHA` and
+/// `BUILD_TIME`. A `GIT_SHA` set in the environment wins, for builds from a
+/// source tarball or a Docker context without `.git`.
+fn emit_build_info() {
+    println!("cargo:rerun-if-env-changed=GIT_SHA");
+    let git_sha = std::env::var("GIT_SHA").ok().filter(|sha| !sha.is_empty()).or_else(|| {
+        let output = Command::new("git").args(["rev-parse", "--short=12", "HEAD"]).output().ok()?;
+        output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    });
+    println!("cargo:rustc-env=GIT_SHA={}", git_sha.as_deref().unwrap_or("unknown"));
+
+    // Pick up checkouts (HEAD) and new commits (the branch HEAD points at); a
+    // path that doesn't exist would make this rerun on every build
+    let head = Path::new(".git/HEAD");
+    if head.exists() {
+        println!("cargo:rerun-if-changed={}", head.display());
+        let branch = std::fs::read_to_string(head).ok().and_then(|h| h.strip_prefix("ref: ").map(|r| r.trim().to_string()));
+        if let Some(branch_ref) = branch.map(|r| Path::new(".git").join(r)).filter(|r| r.exists()) {
+            println!("cargo:rerun-if-changed={}", branch_ref.display());
+        }
+    }
+
+    let build_time = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    println!("cargo:rustc-env=BUILD_TIME={}", build_time);
+}
 
 fn main() {
+    emit_build_info();
+
     // Collect all .proto files under proto/**/*
     let mut protos: Vec<PathBuf> = Vec::new();
     for entry in glob::glob("proto/*.proto").expect("Failed to read glob pattern") {
@@ -25,6 +54,9 @@ fn main() {
     // Example: Keep bytes as Vec<u8> for everything (or restrict with matching paths)
     // cfg.bytes(&["."]);
 
+    // Serialize lets the API return decoded messages as JSON (StandardCosmosMsg::to_json)
+    cfg.type_attribute(".", "#[derive(serde::Serialize)]");
+
     cfg.compile_protos(
         &protos.iter().map(|p| p.as_path()).collect::<Vec<_>>(),
         includes,
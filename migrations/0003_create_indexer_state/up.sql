@@ -0,0 +1,6 @@
+-- Last block height processed by each indexer instance.
+CREATE TABLE IF NOT EXISTS indexer_state (
+    indexer_id TEXT PRIMARY KEY,
+    last_height BIGINT NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,9 @@
+-- Cumulative transaction fees paid per address, one row per fee denom.
+CREATE TABLE IF NOT EXISTS fees_paid (
+    t_address TEXT NOT NULL,
+    denom TEXT NOT NULL,
+    amount BIGINT NOT NULL DEFAULT 0,
+    block BIGINT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (t_address, denom)
+);
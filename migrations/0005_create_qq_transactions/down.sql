@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS qq_tx (
+    tx_hash TEXT NOT NULL,
+    tx TEXT NOT NULL,
+    block BIGINT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (tx_hash, block)
+);
+
+INSERT INTO qq_tx (tx_hash, tx, block, created_at)
+SELECT encode(sha256(convert_to(tx_json, 'UTF8')), 'hex'), tx_json, block_height, created_at
+FROM qq_transactions
+ON CONFLICT DO NOTHING;
+
+DROP TABLE IF EXISTS qq_transactions;
+
+CREATE INDEX IF NOT EXISTS idx_qq_tx_block ON qq_tx(block);
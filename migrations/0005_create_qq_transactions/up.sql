@@ -0,0 +1,26 @@
+-- Replace qq_tx with qq_transactions: a surrogate id plus the decoded variant
+-- name, so rows can be referenced and filtered. Existing rows are carried over.
+CREATE TABLE IF NOT EXISTS qq_transactions (
+    id SERIAL PRIMARY KEY,
+    block_height BIGINT NOT NULL,
+    tx_type TEXT NOT NULL,
+    tx_json TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO qq_transactions (block_height, tx_type, tx_json, created_at)
+SELECT block,
+       CASE
+           WHEN tx::jsonb -> 'tx' ? 'TransactionTransfer' THEN 'Transfer'
+           WHEN tx::jsonb -> 'tx' ? 'TransactionScript' THEN 'Script'
+           WHEN tx::jsonb -> 'tx' ? 'Message' THEN 'Message'
+           ELSE 'Unknown'
+       END,
+       tx,
+       created_at
+FROM qq_tx
+ORDER BY block, created_at;
+
+DROP TABLE IF EXISTS qq_tx;
+
+CREATE INDEX IF NOT EXISTS idx_qq_transactions_block_height ON qq_transactions(block_height);
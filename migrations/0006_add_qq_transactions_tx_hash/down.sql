@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_qq_transactions_tx_hash;
+
+ALTER TABLE qq_transactions DROP COLUMN IF EXISTS tx_hash;
@@ -0,0 +1,6 @@
+-- SHA-256 of the raw tx bytecode, so reprocessing a height can't store the same
+-- transaction twice. Rows written before this migration have no bytecode to hash
+-- and keep a NULL tx_hash (NULLs never conflict).
+ALTER TABLE qq_transactions ADD COLUMN IF NOT EXISTS tx_hash TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_qq_transactions_tx_hash ON qq_transactions(tx_hash);
@@ -0,0 +1,11 @@
+-- Cumulative outgoing IBC transfers per sender, receiver, denom and channel.
+CREATE TABLE IF NOT EXISTS ibc_transfers (
+    t_address TEXT NOT NULL,
+    receiver TEXT NOT NULL,
+    denom TEXT NOT NULL,
+    amount BIGINT NOT NULL DEFAULT 0,
+    source_channel TEXT NOT NULL,
+    block BIGINT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (t_address, receiver, denom, source_channel)
+);
@@ -0,0 +1,13 @@
+-- One row per newly indexed QuisQuis script transaction (order opens/closes and
+-- anything else run through a script), with the decoded script as JSON.
+CREATE TABLE IF NOT EXISTS script_txs (
+    id SERIAL PRIMARY KEY,
+    block_height BIGINT NOT NULL,
+    kind TEXT NOT NULL,
+    from_address TEXT,
+    to_address TEXT,
+    script_json TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_script_txs_block_height ON script_txs(block_height);
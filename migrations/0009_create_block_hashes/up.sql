@@ -0,0 +1,7 @@
+-- Hash of each block as it was indexed, so a later re-fetch can tell whether
+-- the chain has reorganised under an already indexed height (REORG_DEPTH).
+CREATE TABLE IF NOT EXISTS block_hashes (
+    height BIGINT PRIMARY KEY,
+    block_hash TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
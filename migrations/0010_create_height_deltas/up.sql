@@ -0,0 +1,14 @@
+-- Every stat change the indexer applied, per block height (ENABLE_DELTA_LOG).
+-- `detail` carries the rest of the changed row's key (denom, counterpart address, ...).
+CREATE TABLE IF NOT EXISTS height_deltas (
+    id BIGSERIAL PRIMARY KEY,
+    block_height BIGINT NOT NULL,
+    table_name TEXT NOT NULL,
+    t_address TEXT NOT NULL,
+    detail TEXT,
+    delta BIGINT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_height_deltas_block_height ON height_deltas(block_height);
+CREATE INDEX IF NOT EXISTS idx_height_deltas_t_address ON height_deltas(t_address);
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS unknown_msg_types;
@@ -0,0 +1,8 @@
+-- Message type_urls the decoder has no proto for, with how often they were
+-- seen, so missing message types can be found and added.
+CREATE TABLE IF NOT EXISTS unknown_msg_types (
+    type_url TEXT PRIMARY KEY,
+    count BIGINT NOT NULL,
+    last_seen_height BIGINT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
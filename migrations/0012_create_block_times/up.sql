@@ -0,0 +1,8 @@
+-- Header time (UTC) of each indexed block. Per-height tables such as
+-- qq_transactions and height_deltas join on it for time-bucketed queries.
+CREATE TABLE IF NOT EXISTS block_times (
+    height BIGINT PRIMARY KEY,
+    timestamp TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_block_times_timestamp ON block_times(timestamp);
@@ -0,0 +1,11 @@
+-- Heights the indexer moved past without indexing (fetch or decode failed
+-- after every retry). Retried in the background; a row is removed once its
+-- block has been indexed.
+CREATE TABLE IF NOT EXISTS skipped_blocks (
+    height BIGINT PRIMARY KEY,
+    reason TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 1,
+    last_tried TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_skipped_blocks_last_tried ON skipped_blocks(last_tried);
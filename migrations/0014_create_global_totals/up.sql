@@ -0,0 +1,14 @@
+-- Network-wide sums and counts, recomputed every SUMMARY_REFRESH_SECS so the
+-- totals endpoints read one row instead of scanning the stat tables. The
+-- CHECK keeps it to a single row.
+CREATE TABLE IF NOT EXISTS global_totals (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    transaction_count BIGINT NOT NULL,
+    address_count BIGINT NOT NULL,
+    funds_moved BIGINT NOT NULL,
+    lit_minted_sats BIGINT NOT NULL,
+    lit_burned_sats BIGINT NOT NULL,
+    dark_minted_sats BIGINT NOT NULL,
+    dark_burned_sats BIGINT NOT NULL,
+    refreshed_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_dark_burned_sats_q_address;
+DROP INDEX IF EXISTS idx_dark_minted_sats_q_address;
@@ -0,0 +1,5 @@
+-- Dark balances are looked up by QuisQuis account (get_dark_balance, and any
+-- per-q_address read), which scanned both tables. addr_mappings(q_address)
+-- and qq_transactions(block_height) are already indexed by 0001 and 0005.
+CREATE INDEX IF NOT EXISTS idx_dark_minted_sats_q_address ON dark_minted_sats(q_address);
+CREATE INDEX IF NOT EXISTS idx_dark_burned_sats_q_address ON dark_burned_sats(q_address);
@@ -0,0 +1,9 @@
+-- Public key of each address, taken from the first signer of a transaction it
+-- signed. Multisig keys are recorded by type only (pubkey_hex is NULL).
+CREATE TABLE IF NOT EXISTS accounts (
+    t_address TEXT PRIMARY KEY,
+    pubkey_type TEXT NOT NULL,
+    pubkey_hex TEXT,
+    first_seen_height BIGINT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS btc_withdraw_confirmations;
+DROP TABLE IF EXISTS btc_withdrawals;
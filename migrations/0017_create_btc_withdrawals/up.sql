@@ -0,0 +1,27 @@
+-- BTC withdrawal requests (MsgWithdrawBtcRequest) and where each is in its
+-- lifecycle. status is 'requested' until the withdrawal is tied to a confirmed
+-- BTC transaction, then 'confirmed' with that transaction's hash.
+CREATE TABLE IF NOT EXISTS btc_withdrawals (
+    id SERIAL PRIMARY KEY,
+    t_address TEXT NOT NULL,
+    withdraw_address TEXT NOT NULL,
+    reserve_id BIGINT NOT NULL,
+    amount BIGINT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'requested',
+    requested_height BIGINT NOT NULL,
+    btc_tx_hash TEXT,
+    confirmed_height BIGINT,
+    UNIQUE (t_address, withdraw_address, reserve_id, requested_height)
+);
+
+CREATE INDEX IF NOT EXISTS idx_btc_withdrawals_status ON btc_withdrawals(status);
+
+-- MsgConfirmBtcWithdraw: a judge confirming the BTC transaction that paid out
+-- withdrawals. The message doesn't say which requests it paid.
+CREATE TABLE IF NOT EXISTS btc_withdraw_confirmations (
+    btc_tx_hash TEXT PRIMARY KEY,
+    btc_height BIGINT NOT NULL,
+    btc_block_hash TEXT NOT NULL,
+    judge_address TEXT NOT NULL,
+    block BIGINT NOT NULL
+);
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS btc_deposit_addresses;
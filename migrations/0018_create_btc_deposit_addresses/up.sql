@@ -0,0 +1,12 @@
+-- BTC deposit addresses registered to Twilight addresses
+-- (MsgRegisterBtcDepositAddress). A BTC address can only be registered once;
+-- the first registration wins. reserve_address is not carried by the message
+-- and stays NULL until it can be filled from elsewhere.
+CREATE TABLE IF NOT EXISTS btc_deposit_addresses (
+    btc_deposit_address TEXT PRIMARY KEY,
+    twilight_address TEXT NOT NULL,
+    reserve_address TEXT,
+    height BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_btc_deposit_addresses_twilight_address ON btc_deposit_addresses(twilight_address);
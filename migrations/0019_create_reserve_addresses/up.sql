@@ -0,0 +1,11 @@
+-- BTC reserves registered with MsgRegisterReserveAddress. The reserve id is
+-- only assigned in the tx response, so rows are keyed by reserve address; a
+-- re-registration replaces the script, fragment and judge.
+CREATE TABLE IF NOT EXISTS reserve_addresses (
+    reserve_address TEXT PRIMARY KEY,
+    reserve_script TEXT NOT NULL,
+    fragment_id BIGINT NOT NULL,
+    judge_address TEXT NOT NULL,
+    registered_height BIGINT NOT NULL,
+    updated_height BIGINT NOT NULL
+);
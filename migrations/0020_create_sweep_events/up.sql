@@ -0,0 +1,14 @@
+-- Sweep and refund messages of the BTC bridge, one row per message. A sweep is
+-- identified by (reserve_id, round_id); kind is the message (e.g. 'sign_sweep'),
+-- actor the judge, signer or oracle that sent it, and fields the decoded
+-- message as JSON.
+CREATE TABLE IF NOT EXISTS sweep_events (
+    id SERIAL PRIMARY KEY,
+    reserve_id BIGINT NOT NULL,
+    round_id BIGINT NOT NULL,
+    kind TEXT NOT NULL,
+    actor TEXT NOT NULL,
+    fields TEXT NOT NULL,
+    block BIGINT NOT NULL,
+    UNIQUE (reserve_id, round_id, kind, actor, block)
+);
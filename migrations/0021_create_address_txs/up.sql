@@ -0,0 +1,11 @@
+-- Stored QuisQuis transactions per Twilight address: one row for each address
+-- named by the messages of a tx (DecodedTx::involved_addresses) and each
+-- qq_transactions row stored from that tx.
+CREATE TABLE IF NOT EXISTS address_txs (
+    t_address TEXT NOT NULL,
+    qq_tx_id INTEGER NOT NULL REFERENCES qq_transactions (id) ON DELETE CASCADE,
+    height BIGINT NOT NULL,
+    PRIMARY KEY (t_address, qq_tx_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_address_txs_t_address_height ON address_txs(t_address, height);
@@ -0,0 +1,17 @@
+-- Heights whose stats have been applied. Stats are running totals, so each
+-- block may only be applied once: its height is claimed in the same database
+-- transaction as its stats, and a block whose height is already here (e.g. a
+-- backfill over heights the indexer already covered) is skipped.
+CREATE TABLE IF NOT EXISTS indexed_blocks (
+    height BIGINT PRIMARY KEY,
+    indexed_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+-- Heights indexed before this table existed were not recorded one by one:
+-- everything up to the saved height counts as applied, except blocks still
+-- waiting in skipped_blocks.
+INSERT INTO indexed_blocks (height)
+SELECT h
+FROM generate_series(1::BIGINT, (SELECT COALESCE(MAX(last_height), 0) FROM indexer_state)) AS h
+WHERE h NOT IN (SELECT height FROM skipped_blocks)
+ON CONFLICT DO NOTHING;
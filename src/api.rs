@@ -1,18 +1,93 @@
-use actix_web::{web, App, HttpResponse, HttpServer, Responder};
+use actix_web::{http::header, middleware, web, App, HttpRequest, HttpResponse, HttpServer, Responder};
 use actix_cors::Cors;
+use base64::{engine::general_purpose::STANDARD as B64, Engine as _};
+use chrono::{NaiveDateTime, SecondsFormat};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use crate::quis_quis_tx::decode_transaction;
+use crate::transaction_types::inspect_tx_base64_standard;
+use crate::block_types::{BlockFetchError, BlockSource};
+use crate::config::{Config, DEFAULT_DECODE_MAX_INPUT_LEN};
 use crate::db;
-use utoipa::{OpenApi, ToSchema};
+use crate::live;
+use crate::metrics;
+use crate::pubsub_chain;
+use crate::rate_limit;
+use futures_util::{future, stream, StreamExt};
+use lazy_static::lazy_static;
+use log::{debug, error, info, warn};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
+use utoipa::{IntoParams, OpenApi, ToSchema};
 use utoipa_swagger_ui::SwaggerUi;
 
 /// Request payload for decoding a transaction
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct DecodeRequest {
+    /// Transaction bytecode as hex (optionally `0x`-prefixed) or standard/URL-safe base64
     pub tx_byte_code: String,
 }
 
+/// Request payload for decoding several transactions at once
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DecodeBatchRequest {
+    pub items: Vec<DecodeRequest>,
+}
+
+/// Outcome for one batch item, shaped like the single-transaction response
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum DecodeBatchResult {
+    Decoded(DecodeResponse),
+    Failed(ErrorResponse),
+}
+
+/// Per-item results, in request order
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DecodeBatchResponse {
+    pub success: bool,
+    pub results: Vec<DecodeBatchResult>,
+}
+
+/// Request payload for decoding a standard Cosmos transaction
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DecodeTxStandardRequest {
+    /// Base64-encoded `TxRaw`, as found in `block.data.txs`
+    pub tx_base64: String,
+}
+
+/// Response for successful standard transaction decode
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DecodeTxStandardResponse {
+    pub success: bool,
+    /// Memo, fee, hex signatures and messages (`type_url` + fields)
+    pub tx: serde_json::Value,
+}
+
+/// One transaction of a block: its decoded JSON, or why it couldn't be decoded
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BlockTxData {
+    /// Position of the transaction in the block
+    pub index: usize,
+    /// Same shape as `tx` from `/api/decode-tx-standard`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub tx: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A block's transactions, decoded without recording any stats
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BlockTxsResponse {
+    pub success: bool,
+    pub block_height: u64,
+    pub txs: Vec<BlockTxData>,
+}
+
 /// Response for successful transaction decode
 #[derive(Debug, Serialize, ToSchema)]
 pub struct DecodeResponse {
@@ -26,6 +101,34 @@ pub struct DecodeResponse {
 pub struct ErrorResponse {
     pub success: bool,
     pub error: String,
+    /// Stable code for the failure (e.g. `INVALID_HEX`, `BINCODE_FAILED`, `NOT_FOUND`); only set by the decode endpoint and on `404`s.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+}
+
+/// Which build of the service is running
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VersionResponse {
+    /// Crate version from Cargo.toml
+    pub version: String,
+    /// Short commit hash the binary was built from, or `unknown`
+    pub git_sha: String,
+    /// When the build script last ran (RFC 3339, UTC)
+    pub build_time: String,
+}
+
+/// Readiness check result
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    /// `ready`, or `degraded` when the database cannot be reached
+    pub status: String,
+    /// `ok` or `unreachable`
+    pub db: String,
+    /// Last block height processed by the indexer, if known
+    pub indexed_height: Option<u64>,
+    /// Indexer thread in this process: `running`, `restarting` (after a panic),
+    /// `stopped` or `disabled`. Doesn't affect `status`: the API serves either way.
+    pub indexer: String,
 }
 
 /// Response structs for individual endpoints
@@ -130,6 +233,478 @@ pub struct AddressAllDataResponse {
     pub lit_burned_sats: Vec<LitBurnedSatsData>,
 }
 
+/// An amount in one coin denom
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DenomAmount {
+    pub denom: String,
+    pub amount: i64,
+}
+
+/// Aggregated totals for a given address (zero when there is no activity)
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AddressStatsResponse {
+    pub success: bool,
+    pub t_address: String,
+    pub transaction_count: i64,
+    /// Summed across denoms; see `funds_moved_by_denom`
+    pub funds_moved: i64,
+    /// `funds_moved` per denom, in denom order
+    pub funds_moved_by_denom: Vec<DenomAmount>,
+    pub lit_minted_sats: i64,
+    pub lit_burned_sats: i64,
+    pub dark_minted_sats: i64,
+    pub dark_burned_sats: i64,
+}
+
+/// Network-wide totals across every address
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TotalsResponse {
+    pub success: bool,
+    pub transaction_count: i64,
+    pub funds_moved: i64,
+    pub lit_minted_sats: i64,
+    pub lit_burned_sats: i64,
+    pub dark_minted_sats: i64,
+    pub dark_burned_sats: i64,
+    /// When `global_totals` was last refreshed; `null` if summed for this request
+    pub refreshed_at: Option<String>,
+}
+
+/// Sync status and global counts for dashboards
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StatsSummaryResponse {
+    pub success: bool,
+    /// Last height the indexer committed; `null` before the first block
+    pub indexed_height: Option<u64>,
+    /// Chain tip, cached briefly; `null` if the node couldn't be reached
+    pub chain_latest_height: Option<u64>,
+    /// `chain_latest_height - indexed_height`, `null` if either is unknown
+    pub blocks_behind: Option<u64>,
+    pub transaction_count: i64,
+    pub address_count: i64,
+    pub lit_minted_sats: i64,
+    pub dark_minted_sats: i64,
+    /// When the counts were last refreshed; `null` if summed for this request
+    pub totals_refreshed_at: Option<String>,
+}
+
+/// Query parameters for resolving address mappings; exactly one must be set
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AddrMappingQuery {
+    /// Twilight address to list QuisQuis accounts for
+    pub t_address: Option<String>,
+    /// QuisQuis address to list Twilight addresses for
+    pub q_address: Option<String>,
+}
+
+/// Query parameters for a QuisQuis account's dark balance
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DarkBalanceQuery {
+    /// QuisQuis address to net mints against burns for
+    pub q_address: String,
+}
+
+/// Query parameters for listing registered BTC deposit addresses
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BtcDepositAddressesQuery {
+    /// Twilight address the deposit addresses were registered to
+    pub twilight_address: String,
+}
+
+/// Query parameters for listing registered reserves
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReservesQuery {
+    /// Page size (default 50, max 500)
+    pub limit: Option<u32>,
+    /// Number of rows to skip (default 0)
+    pub offset: Option<u32>,
+}
+
+/// Query parameters for an address's stored transactions
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AddressTransactionsQuery {
+    /// Page size (default 50, max 500)
+    pub limit: Option<u32>,
+    /// Number of rows to skip (default 0)
+    pub offset: Option<u32>,
+}
+
+/// Query parameters for the live transaction websocket
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct WsTransactionsQuery {
+    /// Comma-separated message types (e.g. `twilightproject.nyks.zkos.MsgTransferTx`);
+    /// only txs containing one of them are sent. All txs when omitted.
+    #[serde(rename = "type")]
+    #[param(rename = "type")]
+    pub type_filter: Option<String>,
+}
+
+/// Query parameters for a reserve's sweep/refund messages
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SweepEventsQuery {
+    /// Only this sweep round
+    pub round_id: Option<u64>,
+    /// Page size (default 50, max 500)
+    pub limit: Option<u32>,
+    /// Number of rows to skip (default 0)
+    pub offset: Option<u32>,
+}
+
+/// Query parameters for the explorer search box
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchQuery {
+    /// Twilight address, QuisQuis address, block height, or transaction (hex QQ bytecode or base64 TxRaw)
+    pub q: String,
+}
+
+/// A Twilight address and where to find its stats
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchAddressResult {
+    pub t_address: String,
+    /// `/api/address/{t_address}/stats`
+    pub stats_url: String,
+}
+
+/// The Twilight addresses a QuisQuis address maps to
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchQAddressResult {
+    pub q_address: String,
+    pub mappings: Vec<AddrMappingData>,
+}
+
+/// What the indexer knows about a block height
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchBlockResult {
+    pub height: u64,
+    /// Whether the indexer has processed this height
+    pub indexed: bool,
+    /// Header time (RFC 3339, UTC), if the block's time was recorded
+    pub block_time: Option<String>,
+    /// `/api/block/{height}/txs`
+    pub txs_url: String,
+}
+
+/// What a search query resolved to; `kind` says which shape `result` has
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(tag = "kind", content = "result", rename_all = "snake_case")]
+pub enum SearchResult {
+    Address(SearchAddressResult),
+    QAddress(SearchQAddressResult),
+    Block(SearchBlockResult),
+    /// QuisQuis transaction bytecode, decoded as by `/api/decode-transaction`
+    QqTx(DecodeResponse),
+    /// Cosmos `TxRaw`, decoded as by `/api/decode-tx-standard`
+    Tx(DecodeTxStandardResponse),
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchResponse {
+    pub success: bool,
+    #[serde(flatten)]
+    pub found: SearchResult,
+}
+
+/// Dark sats minted and burned for one QuisQuis account; `net` is negative
+/// when the account has only been burned from
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DarkBalanceResponse {
+    pub success: bool,
+    pub q_address: String,
+    pub minted: i64,
+    pub burned: i64,
+    pub net: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BtcDepositAddressData {
+    pub btc_deposit_address: String,
+    /// Reserve the address deposits into; `null` until known
+    pub reserve_address: Option<String>,
+    /// Height of the `MsgRegisterBtcDepositAddress`
+    pub height: i64,
+}
+
+/// BTC deposit addresses registered to a Twilight address, oldest first
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BtcDepositAddressesResponse {
+    pub success: bool,
+    pub twilight_address: String,
+    pub addresses: Vec<BtcDepositAddressData>,
+}
+
+/// A BTC reserve as last registered with `MsgRegisterReserveAddress`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReserveData {
+    pub reserve_address: String,
+    pub reserve_script: String,
+    pub fragment_id: i64,
+    pub judge_address: String,
+    /// Height of the first registration
+    pub registered_height: i64,
+    /// Height of the latest registration
+    pub updated_height: i64,
+}
+
+/// Registered reserves, earliest registration first
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReservesResponse {
+    pub success: bool,
+    pub limit: i64,
+    pub offset: i64,
+    pub reserves: Vec<ReserveData>,
+}
+
+/// One sweep/refund message of the bridge
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SweepEventData {
+    pub round_id: i64,
+    /// Message, e.g. `propose_sweep_address`, `sign_sweep`, `broadcast_tx_refund`
+    pub kind: String,
+    /// Judge, signer or oracle that sent it
+    pub actor: String,
+    /// The decoded message
+    #[schema(value_type = Object)]
+    pub fields: Value,
+    pub block: i64,
+}
+
+/// Sweep/refund messages for a reserve, in chain order
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SweepEventsResponse {
+    pub success: bool,
+    pub reserve_id: u64,
+    pub limit: i64,
+    pub offset: i64,
+    pub events: Vec<SweepEventData>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AddrMappingData {
+    pub t_address: String,
+    pub q_address: String,
+    pub block: i64,
+}
+
+/// Mappings between Twilight and QuisQuis addresses (empty when nothing matches)
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AddrMappingResponse {
+    pub success: bool,
+    pub mappings: Vec<AddrMappingData>,
+}
+
+/// Query parameters for listing stored QuisQuis transactions
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct QQTransactionsQuery {
+    /// Block height to list transactions for
+    pub block_height: u64,
+    /// Page size (default 50, max 500)
+    pub limit: Option<u32>,
+    /// Number of rows to skip (default 0)
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct QQTransactionData {
+    pub id: i32,
+    pub block_height: i64,
+    pub tx_type: String,
+    #[schema(value_type = Object)]
+    pub tx_json: Value,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct QQTransactionsResponse {
+    pub success: bool,
+    pub block_height: u64,
+    /// Header time of the block (RFC 3339, UTC); `null` if the block's time wasn't recorded
+    pub block_time: Option<String>,
+    pub limit: i64,
+    pub offset: i64,
+    pub transactions: Vec<QQTransactionData>,
+}
+
+/// Stored QuisQuis transactions involving an address, lowest height first
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AddressTransactionsResponse {
+    pub success: bool,
+    pub t_address: String,
+    pub limit: i64,
+    pub offset: i64,
+    pub transactions: Vec<QQTransactionData>,
+}
+
+/// A single stored QuisQuis transaction with its metadata
+#[derive(Debug, Serialize, ToSchema)]
+pub struct QQTransactionDetailResponse {
+    pub success: bool,
+    pub id: i32,
+    /// Hash of the transaction bytecode (`null` for rows stored before hashes were recorded)
+    pub tx_hash: Option<String>,
+    pub block_height: i64,
+    pub tx_type: String,
+    /// When the indexer stored the row (RFC 3339, UTC)
+    pub created_at: String,
+    #[schema(value_type = Object)]
+    pub tx_json: Value,
+}
+
+/// Query parameters for ranking addresses by activity
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AddressesQuery {
+    /// `tx_count` (default) or `funds_moved`
+    pub sort: Option<String>,
+    /// `desc` (default) or `asc`
+    pub order: Option<String>,
+    /// Page size (default 50, max 500)
+    pub limit: Option<u32>,
+    /// Number of rows to skip (default 0)
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AddressActivityData {
+    pub t_address: String,
+    pub tx_count: i64,
+    pub funds_moved: i64,
+}
+
+/// A page of addresses with the total number of addresses for paging
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AddressesResponse {
+    pub success: bool,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+    pub addresses: Vec<AddressActivityData>,
+}
+
+/// Query parameters for the top-holders endpoints
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TopQuery {
+    /// Number of addresses to return (default 50, max 500)
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NetMintedData {
+    pub t_address: String,
+    /// QuisQuis account recorded on the mint (dark sats only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub q_address: Option<String>,
+    pub minted: i64,
+    pub burned: i64,
+    /// `minted - burned`; negative when the mint predates the indexed range
+    pub net: i64,
+}
+
+impl From<db::NetMinted> for NetMintedData {
+    fn from(r: db::NetMinted) -> Self {
+        NetMintedData {
+            t_address: r.t_address,
+            q_address: r.q_address,
+            minted: r.minted,
+            burned: r.burned,
+            net: r.net,
+        }
+    }
+}
+
+/// Addresses with the largest net minted sats, largest first
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TopMintedResponse {
+    pub success: bool,
+    pub limit: i64,
+    pub addresses: Vec<NetMintedData>,
+}
+
+/// BTC deposited against BTC withdrawn, network-wide
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReserveHealthResponse {
+    pub success: bool,
+    /// `false` if `net_sats` is negative or any address withdrew more than it deposited
+    pub healthy: bool,
+    pub lit_minted_sats: i64,
+    pub lit_burned_sats: i64,
+    /// `lit_minted_sats - lit_burned_sats`
+    pub net_sats: i64,
+    /// How many addresses withdrew more than they deposited
+    pub overdrawn_count: i64,
+    /// Those addresses, most overdrawn first, up to `limit`
+    pub overdrawn: Vec<NetMintedData>,
+}
+
+/// Query parameters for an address timeline
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TimelineQuery {
+    /// Lowest block height to include
+    pub from_height: Option<u64>,
+    /// Highest block height to include
+    pub to_height: Option<u64>,
+    /// Page size (default 50, max 500)
+    pub limit: Option<u32>,
+    /// Number of rows to skip (default 0)
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TimelineEvent {
+    pub height: i64,
+    /// Header time of the block (RFC 3339, UTC); `null` if the block's time wasn't recorded
+    pub timestamp: Option<String>,
+    /// `tx`, `send`, `lit_mint`, `lit_burn`, `dark_mint`, `dark_burn`, `qq_mapping`, `fee`,
+    /// `ibc_transfer`, `trade`, `order_open` or `order_close`
+    pub event_type: String,
+    /// Sats (or the denom in `detail`) for amounts, 1 for counted events
+    pub amount: i64,
+    /// Rest of the event's key: denom, QuisQuis account or counterpart address
+    pub detail: Option<String>,
+}
+
+/// An address's events, oldest first
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AddressTimelineResponse {
+    pub success: bool,
+    pub t_address: String,
+    pub limit: i64,
+    pub offset: i64,
+    pub events: Vec<TimelineEvent>,
+}
+
+const DEFAULT_PAGE_LIMIT: i64 = 50;
+const MAX_PAGE_LIMIT: i64 = 500;
+
+/// Page size from an optional `limit` query param, clamped to `1..=MAX_PAGE_LIMIT`.
+fn page_limit(limit: Option<u32>) -> i64 {
+    limit.map_or(DEFAULT_PAGE_LIMIT, |l| (l as i64).clamp(1, MAX_PAGE_LIMIT))
+}
+
+/// Sort column and direction (`true` = descending) from the `sort`/`order` query params.
+fn parse_address_sort(sort: Option<&str>, order: Option<&str>) -> Result<(db::AddressSort, bool), String> {
+    let sort = match sort.unwrap_or("tx_count") {
+        "tx_count" => db::AddressSort::TxCount,
+        "funds_moved" => db::AddressSort::FundsMoved,
+        other => return Err(format!("Invalid sort {:?}: expected tx_count or funds_moved", other)),
+    };
+    let descending = match order.unwrap_or("desc") {
+        "desc" => true,
+        "asc" => false,
+        other => return Err(format!("Invalid order {:?}: expected asc or desc", other)),
+    };
+    Ok((sort, descending))
+}
+
 /// Convert opcode byte to instruction name
 fn opcode_to_name(opcode: u8) -> &'static str {
     match opcode {
@@ -477,20 +1052,10 @@ fn extract_tx_summary(data: &Value) -> Value {
     Value::Object(summary)
 }
 
-/// API endpoint: POST /api/decode-transaction
-///
-/// Example request:
-/// ```json
-/// {
-///   "tx_byte_code": "0x123abc...",
-///   "block_height": 12345
-/// }
-/// ```
-async fn decode_transaction_endpoint(
-    req: web::Json<DecodeRequest>,
-) -> impl Responder {
-
-    match decode_transaction(&req.tx_byte_code) {
+/// Decodes QQ transaction bytecode into what `/api/decode-transaction` returns,
+/// or the error it reports.
+fn decode_tx_response(tx_byte_code: &str) -> Result<DecodeResponse, ErrorResponse> {
+    match decode_transaction(tx_byte_code) {
         Ok(decoded_tx) => {
             let mut data = serde_json::to_value(&decoded_tx).unwrap_or(serde_json::json!({}));
 
@@ -520,61 +1085,314 @@ async fn decode_transaction_endpoint(
                 map.insert("summary".to_string(), summary);
             }
 
-            HttpResponse::Ok().json(DecodeResponse {
+            Ok(DecodeResponse {
                 success: true,
                 tx_type: tx_type.to_string(),
                 data,
             })
         }
         Err(e) => {
-            eprintln!("❌ Failed to decode transaction: {:?}", e);
-            HttpResponse::BadRequest().json(ErrorResponse {
+            error!("Failed to decode transaction: {:?}", e);
+            Err(ErrorResponse {
                 success: false,
                 error: format!("Failed to decode transaction: {}", e),
+                error_code: Some(e.code().as_str().to_string()),
             })
         }
     }
 }
 
-/// API endpoint: GET /api/transactions/{t_address}
-#[utoipa::path(
-    get,
-    path = "/api/transactions/{t_address}",
-    params(
-        ("t_address" = String, Path, description = "Twilight address to query transactions for")
-    ),
-    responses(
-        (status = 200, description = "Successfully retrieved transactions", body = TransactionsResponse),
-        (status = 500, description = "Internal server error", body = ErrorResponse)
-    ),
-    tag = "Transactions"
-)]
-async fn get_transactions(path: web::Path<String>) -> impl Responder {
-    let t_address = path.into_inner();
+/// Room for the JSON around `tx_byte_code` in a decode request body.
+const DECODE_BODY_OVERHEAD: usize = 4 * 1024;
 
-    match db::get_transactions_by_address(&t_address) {
-        Ok(records) => {
-            let transaction_count = records.len() as i64;
+/// JSON body limit for the decode endpoints: a `tx_byte_code` of
+/// `max_input_len` chars plus the JSON around it. Larger bodies get `413`
+/// before they are read in full.
+fn decode_json_config(max_input_len: usize) -> web::JsonConfig {
+    web::JsonConfig::default().limit(max_input_len.saturating_add(DECODE_BODY_OVERHEAD))
+}
 
-            HttpResponse::Ok().json(TransactionsResponse {
-                success: true,
-                t_address,
-                transaction_count,
+/// `DECODE_MAX_INPUT_LEN` from the app's config, or its default when the app
+/// has none (as in tests).
+fn decode_max_input_len(req: &HttpRequest) -> usize {
+    req.app_data::<web::Data<Config>>()
+        .map_or(DEFAULT_DECODE_MAX_INPUT_LEN, |config| config.decode_max_input_len)
+}
+
+/// API endpoint: POST /api/decode-transaction
+///
+/// Decodes only: nothing is written to `qq_transactions` or the stats tables,
+/// so the request carries no block height. A `tx_byte_code` longer than
+/// `DECODE_MAX_INPUT_LEN` is rejected with `413` before decoding.
+///
+/// Example request:
+/// ```json
+/// {
+///   "tx_byte_code": "0x123abc..."
+/// }
+/// ```
+async fn decode_transaction_endpoint(
+    http_req: HttpRequest,
+    req: web::Json<DecodeRequest>,
+) -> impl Responder {
+    let max_len = decode_max_input_len(&http_req);
+    if req.tx_byte_code.len() > max_len {
+        return HttpResponse::PayloadTooLarge().json(ErrorResponse {
+            success: false,
+            error: format!("tx_byte_code is {} chars, at most {} are accepted", req.tx_byte_code.len(), max_len),
+            error_code: None,
+        });
+    }
+    match decode_tx_response(&req.tx_byte_code) {
+        Ok(decoded) => HttpResponse::Ok().json(decoded),
+        Err(error) => HttpResponse::BadRequest().json(error),
+    }
+}
+
+/// Most transactions `/api/decode-transactions` accepts in one request.
+const MAX_DECODE_BATCH: usize = 100;
+
+/// Request body allowed for a batch: 100 transfers with large proofs, hex encoded.
+const DECODE_BATCH_BODY_LIMIT: usize = 16 * 1024 * 1024;
+
+/// API endpoint: POST /api/decode-transactions
+///
+/// Decodes up to `MAX_DECODE_BATCH` transactions in one round trip. Each entry
+/// of `results` is exactly what `/api/decode-transaction` would return for
+/// that item, so one bad transaction doesn't fail the others.
+///
+/// Example request:
+/// ```json
+/// {
+///   "items": [{ "tx_byte_code": "0x123abc..." }, { "tx_byte_code": "0x456def..." }]
+/// }
+/// ```
+async fn decode_transactions_endpoint(
+    req: web::Json<DecodeBatchRequest>,
+) -> impl Responder {
+    let items = req.into_inner().items;
+    if items.len() > MAX_DECODE_BATCH {
+        return HttpResponse::PayloadTooLarge().json(ErrorResponse {
+            success: false,
+            error: format!("At most {} transactions per batch, got {}", MAX_DECODE_BATCH, items.len()),
+            error_code: None,
+        });
+    }
+
+    // Decoding is CPU-bound, so keep a large batch off the async workers
+    let decoded = web::block(move || {
+        items
+            .iter()
+            .map(|item| match decode_tx_response(&item.tx_byte_code) {
+                Ok(decoded) => DecodeBatchResult::Decoded(decoded),
+                Err(error) => DecodeBatchResult::Failed(error),
             })
-        }
+            .collect()
+    })
+    .await;
+    match decoded {
+        Ok(results) => HttpResponse::Ok().json(DecodeBatchResponse { success: true, results }),
         Err(e) => {
-            eprintln!("❌ Failed to fetch transactions: {:?}", e);
+            error!("Failed to decode transaction batch: {:?}", e);
             HttpResponse::InternalServerError().json(ErrorResponse {
                 success: false,
-                error: format!("Failed to fetch transactions: {}", e),
+                error: format!("Failed to decode transaction batch: {}", e),
+                error_code: None,
             })
         }
     }
 }
 
-/// API endpoint: GET /api/funding/{t_address}
-#[utoipa::path(
-    get,
+/// API endpoint: POST /api/decode-tx-standard
+///
+/// Decodes a full Cosmos `TxRaw` without recording any stats.
+///
+/// Example request:
+/// ```json
+/// {
+///   "tx_base64": "CpIBCo8BChwvY29zbW9zLmJhbmsu..."
+/// }
+/// ```
+async fn decode_tx_standard_endpoint(
+    req: web::Json<DecodeTxStandardRequest>,
+) -> impl Responder {
+    match inspect_tx_base64_standard(&req.tx_base64) {
+        Ok(decoded_tx) => HttpResponse::Ok().json(DecodeTxStandardResponse {
+            success: true,
+            tx: decoded_tx.to_json(),
+        }),
+        Err(e) => {
+            error!("Failed to decode standard transaction: {:?}", e);
+            HttpResponse::BadRequest().json(ErrorResponse {
+                success: false,
+                error: format!("Failed to decode transaction: {}", e),
+                error_code: None,
+            })
+        }
+    }
+}
+
+/// Decodes each of a block's base64 txs on its own, keeping failures inline.
+fn decode_block_txs(txs: &[String]) -> Vec<BlockTxData> {
+    txs.iter()
+        .enumerate()
+        .map(|(index, tx)| match inspect_tx_base64_standard(tx) {
+            Ok(decoded) => BlockTxData { index, tx: Some(decoded.to_json()), error: None },
+            Err(e) => BlockTxData { index, tx: None, error: Some(e.to_string()) },
+        })
+        .collect()
+}
+
+/// API endpoint: GET /api/block/{height}/txs
+///
+/// Fetches a block from the chain and decodes its transactions without
+/// recording any stats. A transaction that fails to decode carries an `error`
+/// instead of failing the whole request.
+#[utoipa::path(
+    get,
+    path = "/api/block/{height}/txs",
+    params(
+        ("height" = u64, Path, description = "Block height to fetch and decode")
+    ),
+    responses(
+        (status = 200, description = "Successfully decoded the block's transactions", body = BlockTxsResponse),
+        (status = 404, description = "The node doesn't have a block at this height", body = ErrorResponse),
+        (status = 502, description = "The block couldn't be fetched from the chain", body = ErrorResponse)
+    ),
+    tag = "Transactions"
+)]
+async fn get_block_txs(path: web::Path<u64>) -> impl Responder {
+    let height = path.into_inner();
+
+    // The chain client is blocking, so keep it off the async workers
+    let fetched = match web::block(move || pubsub_chain::chain().block_at(height)).await {
+        Ok(fetched) => fetched,
+        Err(e) => Err(BlockFetchError::Transient(e.to_string())),
+    };
+    match fetched {
+        Ok(block_raw) => HttpResponse::Ok().json(BlockTxsResponse {
+            success: true,
+            block_height: height,
+            txs: decode_block_txs(&block_raw.block.data.txs),
+        }),
+        Err(e @ (BlockFetchError::NotFound | BlockFetchError::HeightNotYetAvailable)) => {
+            HttpResponse::NotFound().json(ErrorResponse {
+                success: false,
+                error: format!("No block at height {}: {}", height, e),
+                error_code: Some("NOT_FOUND".to_string()),
+            })
+        }
+        Err(e) => {
+            error!("Failed to fetch block {}: {}", height, e);
+            HttpResponse::BadGateway().json(ErrorResponse {
+                success: false,
+                error: format!("Failed to fetch block {}: {}", height, e),
+                error_code: None,
+            })
+        }
+    }
+}
+
+/// API endpoint: GET /api/ws/transactions?type=
+/// Websocket that pushes each newly indexed tx as a JSON text frame,
+/// `{"height", "tx_index", "types", "messages"}`. A client that falls too far
+/// behind skips the txs it missed and gets `{"lagged": <count>}` instead.
+#[utoipa::path(
+    get,
+    path = "/api/ws/transactions",
+    params(WsTransactionsQuery),
+    responses(
+        (status = 101, description = "Switched to the websocket protocol"),
+        (status = 400, description = "Not a websocket upgrade request")
+    ),
+    tag = "Transactions"
+)]
+async fn ws_transactions(
+    req: HttpRequest,
+    body: web::Payload,
+    query: web::Query<WsTransactionsQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let (response, mut session, mut msg_stream) = actix_ws::handle(&req, body)?;
+    let filter = live::parse_type_filter(query.type_filter.as_deref());
+    let mut rx = live::subscribe();
+
+    actix_web::rt::spawn(async move {
+        loop {
+            tokio::select! {
+                msg = msg_stream.recv() => match msg {
+                    Some(Ok(actix_ws::Message::Ping(bytes))) => {
+                        if session.pong(&bytes).await.is_err() {
+                            return;
+                        }
+                    }
+                    Some(Ok(actix_ws::Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                },
+                tx = rx.recv() => match tx {
+                    Ok(tx) if tx.has_type(&filter) => {
+                        let frame = serde_json::to_string(&*tx).unwrap_or_default();
+                        if session.text(frame).await.is_err() {
+                            return;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(missed)) => {
+                        debug!("Websocket client lagged, skipped {} txs", missed);
+                        let notice = serde_json::json!({ "lagged": missed }).to_string();
+                        if session.text(notice).await.is_err() {
+                            return;
+                        }
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+            }
+        }
+        let _ = session.close(None).await;
+    });
+
+    Ok(response)
+}
+
+/// API endpoint: GET /api/transactions/{t_address}
+#[utoipa::path(
+    get,
+    path = "/api/transactions/{t_address}",
+    params(
+        ("t_address" = String, Path, description = "Twilight address to query transactions for")
+    ),
+    responses(
+        (status = 200, description = "Successfully retrieved transactions", body = TransactionsResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "Transactions"
+)]
+async fn get_transactions(path: web::Path<String>) -> impl Responder {
+    let t_address = path.into_inner();
+
+    match db::get_transactions_by_address(&t_address) {
+        Ok(records) => {
+            let transaction_count = records.len() as i64;
+
+            HttpResponse::Ok().json(TransactionsResponse {
+                success: true,
+                t_address,
+                transaction_count,
+            })
+        }
+        Err(e) => {
+            error!("Failed to fetch transactions: {:?}", e);
+            HttpResponse::InternalServerError().json(ErrorResponse {
+                success: false,
+                error: format!("Failed to fetch transactions: {}", e),
+                error_code: None,
+            })
+        }
+    }
+}
+
+/// API endpoint: GET /api/funding/{t_address}
+#[utoipa::path(
+    get,
     path = "/api/funding/{t_address}",
     params(
         ("t_address" = String, Path, description = "Twilight address to query total amount of funds moved between funding accounts")
@@ -606,10 +1424,11 @@ async fn get_funds_moved(path: web::Path<String>) -> impl Responder {
             })
         }
         Err(e) => {
-            eprintln!("❌ Failed to fetch funds moved: {:?}", e);
+            error!("Failed to fetch funds moved: {:?}", e);
             HttpResponse::InternalServerError().json(ErrorResponse {
                 success: false,
                 error: format!("Failed to fetch funds moved: {}", e),
+                error_code: None,
             })
         }
     }
@@ -649,10 +1468,11 @@ async fn get_dark_burned_sats(path: web::Path<String>) -> impl Responder {
             })
         }
         Err(e) => {
-            eprintln!("❌ Failed to fetch dark burned sats: {:?}", e);
+            error!("Failed to fetch dark burned sats: {:?}", e);
             HttpResponse::InternalServerError().json(ErrorResponse {
                 success: false,
                 error: format!("Failed to fetch dark burned sats: {}", e),
+                error_code: None,
             })
         }
     }
@@ -692,10 +1512,11 @@ async fn get_dark_minted_sats(path: web::Path<String>) -> impl Responder {
             })
         }
         Err(e) => {
-            eprintln!("❌ Failed to fetch dark minted sats: {:?}", e);
+            error!("Failed to fetch dark minted sats: {:?}", e);
             HttpResponse::InternalServerError().json(ErrorResponse {
                 success: false,
                 error: format!("Failed to fetch dark minted sats: {}", e),
+                error_code: None,
             })
         }
     }
@@ -734,10 +1555,11 @@ async fn get_lit_minted_sats(path: web::Path<String>) -> impl Responder {
             })
         }
         Err(e) => {
-            eprintln!("❌ Failed to fetch lit minted sats: {:?}", e);
+            error!("Failed to fetch lit minted sats: {:?}", e);
             HttpResponse::InternalServerError().json(ErrorResponse {
                 success: false,
                 error: format!("Failed to fetch lit minted sats: {}", e),
+                error_code: None,
             })
         }
     }
@@ -776,10 +1598,11 @@ async fn get_lit_burned_sats(path: web::Path<String>) -> impl Responder {
             })
         }
         Err(e) => {
-            eprintln!("❌ Failed to fetch lit burned sats: {:?}", e);
+            error!("Failed to fetch lit burned sats: {:?}", e);
             HttpResponse::InternalServerError().json(ErrorResponse {
                 success: false,
                 error: format!("Failed to fetch lit burned sats: {}", e),
+                error_code: None,
             })
         }
     }
@@ -818,10 +1641,356 @@ async fn get_q_addresses(path: web::Path<String>) -> impl Responder {
             })
         }
         Err(e) => {
-            eprintln!("❌ Failed to fetch q addresses: {:?}", e);
+            error!("Failed to fetch q addresses: {:?}", e);
             HttpResponse::InternalServerError().json(ErrorResponse {
                 success: false,
                 error: format!("Failed to fetch q addresses: {}", e),
+                error_code: None,
+            })
+        }
+    }
+}
+
+/// API endpoint: GET /api/btc-deposit-addresses?twilight_address=
+/// Lists the BTC deposit addresses registered to a Twilight address
+#[utoipa::path(
+    get,
+    path = "/api/btc-deposit-addresses",
+    params(BtcDepositAddressesQuery),
+    responses(
+        (status = 200, description = "Successfully retrieved BTC deposit addresses", body = BtcDepositAddressesResponse),
+        (status = 400, description = "Invalid twilight_address", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "BTC Deposited"
+)]
+async fn get_btc_deposit_addresses(query: web::Query<BtcDepositAddressesQuery>) -> impl Responder {
+    let twilight_address = query.into_inner().twilight_address;
+    if !db::is_valid_t_address(&twilight_address) {
+        return HttpResponse::BadRequest().json(ErrorResponse {
+            success: false,
+            error: "twilight_address must be a twilight1... address".to_string(),
+            error_code: None,
+        });
+    }
+
+    match db::get_btc_deposit_addresses(&twilight_address) {
+        Ok(records) => HttpResponse::Ok().json(BtcDepositAddressesResponse {
+            success: true,
+            twilight_address,
+            addresses: records
+                .into_iter()
+                .map(|r| BtcDepositAddressData {
+                    btc_deposit_address: r.btc_deposit_address,
+                    reserve_address: r.reserve_address,
+                    height: r.height,
+                })
+                .collect(),
+        }),
+        Err(e) => {
+            error!("Failed to fetch BTC deposit addresses: {:?}", e);
+            HttpResponse::InternalServerError().json(ErrorResponse {
+                success: false,
+                error: format!("Failed to fetch BTC deposit addresses: {}", e),
+                error_code: None,
+            })
+        }
+    }
+}
+
+/// API endpoint: GET /api/reserves
+/// Lists BTC reserves registered on the bridge
+#[utoipa::path(
+    get,
+    path = "/api/reserves",
+    params(ReservesQuery),
+    responses(
+        (status = 200, description = "Successfully retrieved reserves", body = ReservesResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "BTC Deposited"
+)]
+async fn get_reserves(query: web::Query<ReservesQuery>) -> impl Responder {
+    let limit = page_limit(query.limit);
+    let offset = query.offset.unwrap_or(0) as i64;
+
+    match db::get_reserve_addresses(limit, offset) {
+        Ok(rows) => HttpResponse::Ok().json(ReservesResponse {
+            success: true,
+            limit,
+            offset,
+            reserves: rows
+                .into_iter()
+                .map(|r| ReserveData {
+                    reserve_address: r.reserve_address,
+                    reserve_script: r.reserve_script,
+                    fragment_id: r.fragment_id,
+                    judge_address: r.judge_address,
+                    registered_height: r.registered_height,
+                    updated_height: r.updated_height,
+                })
+                .collect(),
+        }),
+        Err(e) => {
+            error!("Failed to list reserves: {:?}", e);
+            HttpResponse::InternalServerError().json(ErrorResponse {
+                success: false,
+                error: format!("Failed to list reserves: {}", e),
+                error_code: None,
+            })
+        }
+    }
+}
+
+/// API endpoint: GET /api/sweeps/{reserve_id}/events
+/// Lists the sweep and refund messages of a reserve, so a round's signing can be followed
+#[utoipa::path(
+    get,
+    path = "/api/sweeps/{reserve_id}/events",
+    params(
+        ("reserve_id" = u64, Path, description = "Reserve being swept"),
+        SweepEventsQuery
+    ),
+    responses(
+        (status = 200, description = "Successfully retrieved sweep events", body = SweepEventsResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "BTC Withdrawn"
+)]
+async fn get_sweep_events(path: web::Path<u64>, query: web::Query<SweepEventsQuery>) -> impl Responder {
+    let reserve_id = path.into_inner();
+    let limit = page_limit(query.limit);
+    let offset = query.offset.unwrap_or(0) as i64;
+
+    match db::get_sweep_events(reserve_id, query.round_id, limit, offset) {
+        Ok(rows) => HttpResponse::Ok().json(SweepEventsResponse {
+            success: true,
+            reserve_id,
+            limit,
+            offset,
+            events: rows
+                .into_iter()
+                .map(|r| SweepEventData {
+                    round_id: r.round_id,
+                    kind: r.kind,
+                    actor: r.actor,
+                    fields: serde_json::from_str(&r.fields).unwrap_or(Value::Null),
+                    block: r.block,
+                })
+                .collect(),
+        }),
+        Err(e) => {
+            error!("Failed to fetch sweep events: {:?}", e);
+            HttpResponse::InternalServerError().json(ErrorResponse {
+                success: false,
+                error: format!("Failed to fetch sweep events: {}", e),
+                error_code: None,
+            })
+        }
+    }
+}
+
+/// API endpoint: GET /api/addr-mapping?t_address= or ?q_address=
+/// Resolves in either direction between Twilight and QuisQuis addresses
+#[utoipa::path(
+    get,
+    path = "/api/addr-mapping",
+    params(AddrMappingQuery),
+    responses(
+        (status = 200, description = "Successfully retrieved address mappings", body = AddrMappingResponse),
+        (status = 400, description = "Neither or both of t_address and q_address given", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "Twilight/qq mapping"
+)]
+async fn get_addr_mapping(query: web::Query<AddrMappingQuery>) -> impl Responder {
+    let records = match (&query.t_address, &query.q_address) {
+        (Some(t_address), None) => db::get_qaddresses_for_taddress(t_address),
+        (None, Some(q_address)) => db::get_taddresses_for_qaddress(q_address),
+        _ => {
+            return HttpResponse::BadRequest().json(ErrorResponse {
+                success: false,
+                error: "Pass exactly one of t_address or q_address".to_string(),
+                error_code: None,
+            });
+        }
+    };
+
+    match records {
+        Ok(records) => HttpResponse::Ok().json(AddrMappingResponse {
+            success: true,
+            mappings: records
+                .into_iter()
+                .map(|r| AddrMappingData {
+                    t_address: r.t_address,
+                    q_address: r.q_address,
+                    block: r.block,
+                })
+                .collect(),
+        }),
+        Err(e) => {
+            error!("Failed to fetch address mappings: {:?}", e);
+            HttpResponse::InternalServerError().json(ErrorResponse {
+                success: false,
+                error: format!("Failed to fetch address mappings: {}", e),
+                error_code: None,
+            })
+        }
+    }
+}
+
+/// API endpoint: GET /api/dark-balance?q_address=
+/// Returns net dark sats (minted minus burned) for a QuisQuis account
+#[utoipa::path(
+    get,
+    path = "/api/dark-balance",
+    params(DarkBalanceQuery),
+    responses(
+        (status = 200, description = "Successfully retrieved dark balance", body = DarkBalanceResponse),
+        (status = 400, description = "Invalid q_address", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "Twilight/qq mapping"
+)]
+async fn get_dark_balance(query: web::Query<DarkBalanceQuery>) -> impl Responder {
+    let q_address = query.into_inner().q_address;
+    if !db::is_valid_q_address(&q_address) {
+        return HttpResponse::BadRequest().json(ErrorResponse {
+            success: false,
+            error: "q_address must be a hex-encoded QuisQuis address".to_string(),
+            error_code: None,
+        });
+    }
+
+    match db::get_dark_balance(&q_address) {
+        Ok((minted, burned)) => HttpResponse::Ok().json(DarkBalanceResponse {
+            success: true,
+            q_address,
+            minted,
+            burned,
+            net: minted.saturating_sub(burned),
+        }),
+        Err(e) => {
+            error!("Failed to fetch dark balance: {:?}", e);
+            HttpResponse::InternalServerError().json(ErrorResponse {
+                success: false,
+                error: format!("Failed to fetch dark balance: {}", e),
+                error_code: None,
+            })
+        }
+    }
+}
+
+/// What a search query looks like, judged from its characters alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SearchGuess {
+    Height(u64),
+    TAddress,
+    /// Hex: a QuisQuis address, or else QQ transaction bytecode
+    Hex,
+    /// Base64: a Cosmos `TxRaw`, or else QQ transaction bytecode
+    Base64,
+    Unknown,
+}
+
+/// Shortest base64 worth trying as a `TxRaw`; shorter strings are typos, not transactions.
+const MIN_SEARCH_TX_LEN: usize = 16;
+
+fn classify_search(q: &str) -> SearchGuess {
+    if !q.is_empty() && q.chars().all(|c| c.is_ascii_digit()) {
+        // Digits are hex too, so heights are checked first
+        return q.parse().map_or(SearchGuess::Unknown, SearchGuess::Height);
+    }
+    if db::is_valid_t_address(q) {
+        return SearchGuess::TAddress;
+    }
+    if db::is_valid_q_address(q.strip_prefix("0x").unwrap_or(q)) {
+        return SearchGuess::Hex;
+    }
+    if q.len() >= MIN_SEARCH_TX_LEN && B64.decode(q).is_ok() {
+        return SearchGuess::Base64;
+    }
+    SearchGuess::Unknown
+}
+
+fn search_not_found(q: &str) -> HttpResponse {
+    HttpResponse::NotFound().json(ErrorResponse {
+        success: false,
+        error: format!("Nothing matches {:?}", q),
+        error_code: Some("NOT_FOUND".to_string()),
+    })
+}
+
+/// Resolves a search the database can answer (or `None` if it didn't match anything).
+fn search_lookup(q: &str, guess: SearchGuess) -> anyhow::Result<Option<SearchResult>> {
+    match guess {
+        SearchGuess::Height(height) => {
+            let indexed = db::get_last_indexed_height()?.is_some_and(|last| height <= last);
+            let block_time = db::get_block_time(height)?;
+            Ok(Some(SearchResult::Block(SearchBlockResult {
+                height,
+                indexed,
+                block_time: block_time.map(|t| t.and_utc().to_rfc3339_opts(SecondsFormat::Secs, true)),
+                txs_url: format!("/api/block/{}/txs", height),
+            })))
+        }
+        SearchGuess::TAddress => Ok(Some(SearchResult::Address(SearchAddressResult {
+            t_address: q.to_string(),
+            stats_url: format!("/api/address/{}/stats", q),
+        }))),
+        SearchGuess::Hex => {
+            let q_address = q.strip_prefix("0x").unwrap_or(q);
+            let mappings = db::get_taddresses_for_qaddress(q_address)?;
+            if !mappings.is_empty() {
+                return Ok(Some(SearchResult::QAddress(SearchQAddressResult {
+                    q_address: q_address.to_string(),
+                    mappings: mappings
+                        .into_iter()
+                        .map(|m| AddrMappingData { t_address: m.t_address, q_address: m.q_address, block: m.block })
+                        .collect(),
+                })));
+            }
+            // Not a known account, so maybe transaction bytecode
+            Ok(decode_tx_response(q).ok().map(SearchResult::QqTx))
+        }
+        SearchGuess::Base64 => match inspect_tx_base64_standard(q) {
+            Ok(tx) => Ok(Some(SearchResult::Tx(DecodeTxStandardResponse { success: true, tx: tx.to_json() }))),
+            // QQ bytecode may be base64 too
+            Err(_) => Ok(decode_tx_response(q).ok().map(SearchResult::QqTx)),
+        },
+        SearchGuess::Unknown => Ok(None),
+    }
+}
+
+/// API endpoint: GET /api/search?q=
+///
+/// One lookup for an explorer search box. Digits are a block height, `twilight1…`
+/// an address (answered with its stats link), hex a QuisQuis address with known
+/// mappings or else QQ transaction bytecode, and base64 a Cosmos transaction or
+/// else QQ bytecode.
+/// Transactions are decoded for preview only.
+#[utoipa::path(
+    get,
+    path = "/api/search",
+    params(SearchQuery),
+    responses(
+        (status = 200, description = "What the query resolved to", body = SearchResponse),
+        (status = 404, description = "The query matches nothing", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "Stats"
+)]
+async fn search(query: web::Query<SearchQuery>) -> impl Responder {
+    let q = query.q.trim();
+    match search_lookup(q, classify_search(q)) {
+        Ok(Some(found)) => HttpResponse::Ok().json(SearchResponse { success: true, found }),
+        Ok(None) => search_not_found(q),
+        Err(e) => {
+            error!("Failed to search for {:?}: {:?}", q, e);
+            HttpResponse::InternalServerError().json(ErrorResponse {
+                success: false,
+                error: format!("Failed to search: {}", e),
+                error_code: None,
             })
         }
     }
@@ -918,44 +2087,1167 @@ async fn get_address_all_data(path: web::Path<String>) -> impl Responder {
             })
         }
         _ => {
-            eprintln!("❌ Failed to fetch complete address data");
+            error!("Failed to fetch complete address data");
             HttpResponse::InternalServerError().json(ErrorResponse {
                 success: false,
                 error: "Failed to fetch complete address data".to_string(),
+                error_code: None,
             })
         }
     }
 }
 
-/// Health check endpoint
+/// API endpoint: GET /api/address/{t_address}/stats
+/// Returns summed totals for a given t_address across all per-address tables
 #[utoipa::path(
     get,
-    path = "/api/health",
+    path = "/api/address/{t_address}/stats",
+    params(
+        ("t_address" = String, Path, description = "Twilight address to query aggregated totals for")
+    ),
     responses(
-        (status = 200, description = "Service is healthy")
+        (status = 200, description = "Successfully retrieved address totals", body = AddressStatsResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
-    tag = "Health"
+    tag = "Stats"
 )]
-async fn health_check() -> impl Responder {
-    HttpResponse::Ok().json(serde_json::json!({
-        "status": "healthy",
-        "service": "twilight-indexer-api"
-    }))
+async fn get_address_stats(path: web::Path<String>) -> impl Responder {
+    let t_address = path.into_inner();
+
+    let totals = (|| -> anyhow::Result<AddressStatsResponse> {
+        Ok(AddressStatsResponse {
+            success: true,
+            transaction_count: db::get_transaction_count(&t_address)?,
+            funds_moved: db::get_funds_moved_total(&t_address)?.unwrap_or(0),
+            funds_moved_by_denom: db::get_funds_moved_totals_by_denom(&t_address)?
+                .into_iter()
+                .map(|(denom, amount)| DenomAmount { denom, amount })
+                .collect(),
+            lit_minted_sats: db::get_lit_minted_sats_total(&t_address)?.unwrap_or(0),
+            lit_burned_sats: db::get_lit_burned_sats_total(&t_address)?.unwrap_or(0),
+            dark_minted_sats: db::get_dark_minted_sats_total(&t_address)?.unwrap_or(0),
+            dark_burned_sats: db::get_dark_burned_sats_total(&t_address)?.unwrap_or(0),
+            t_address: t_address.clone(),
+        })
+    })();
+
+    match totals {
+        Ok(stats) => HttpResponse::Ok().json(stats),
+        Err(e) => {
+            error!("Failed to fetch address stats: {:?}", e);
+            HttpResponse::InternalServerError().json(ErrorResponse {
+                success: false,
+                error: format!("Failed to fetch address stats: {}", e),
+                error_code: None,
+            })
+        }
+    }
 }
 
-/// OpenAPI documentation
-#[derive(OpenApi)]
-#[openapi(
-    paths(
-        health_check,
-        get_transactions,
-        get_funds_moved,
-        get_dark_burned_sats,
-        get_dark_minted_sats,
-        get_lit_minted_sats,
+/// Timeline event type for a change logged against `table`.
+fn timeline_event_type(table: &str) -> &str {
+    match table {
+        "transactions" => "tx",
+        "funds_moved" => "send",
+        "lit_minted_sats" => "lit_mint",
+        "lit_burned_sats" => "lit_burn",
+        "dark_minted_sats" => "dark_mint",
+        "dark_burned_sats" => "dark_burn",
+        "addr_mappings" => "qq_mapping",
+        "fees_paid" => "fee",
+        "ibc_transfers" => "ibc_transfer",
+        "trading_tx" => "trade",
+        "order_open_tx" => "order_open",
+        "order_close_tx" => "order_close",
+        other => other,
+    }
+}
+
+/// API endpoint: GET /api/address/{t_address}/transactions?limit=&offset=
+/// Returns the stored QuisQuis transactions whose Cosmos tx involved the address, in height order
+#[utoipa::path(
+    get,
+    path = "/api/address/{t_address}/transactions",
+    params(
+        ("t_address" = String, Path, description = "Twilight address to list transactions for"),
+        AddressTransactionsQuery
+    ),
+    responses(
+        (status = 200, description = "Successfully retrieved the address's transactions", body = AddressTransactionsResponse),
+        (status = 400, description = "Invalid t_address", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "QuisQuis Transactions"
+)]
+async fn get_address_transactions(path: web::Path<String>, query: web::Query<AddressTransactionsQuery>) -> impl Responder {
+    let t_address = path.into_inner();
+    if !db::is_valid_t_address(&t_address) {
+        return HttpResponse::BadRequest().json(ErrorResponse {
+            success: false,
+            error: "t_address must be a twilight1... address".to_string(),
+            error_code: None,
+        });
+    }
+    let limit = page_limit(query.limit);
+    let offset = query.offset.unwrap_or(0) as i64;
+
+    match db::get_address_txs(&t_address, limit, offset) {
+        Ok(records) => HttpResponse::Ok().json(AddressTransactionsResponse {
+            success: true,
+            t_address,
+            limit,
+            offset,
+            transactions: records
+                .into_iter()
+                .map(|r| QQTransactionData {
+                    id: r.id,
+                    block_height: r.block_height,
+                    tx_type: r.tx_type,
+                    tx_json: serde_json::from_str(&r.tx_json).unwrap_or(Value::String(r.tx_json)),
+                })
+                .collect(),
+        }),
+        Err(e) => {
+            error!("Failed to fetch transactions for {}: {:?}", t_address, e);
+            HttpResponse::InternalServerError().json(ErrorResponse {
+                success: false,
+                error: format!("Failed to fetch address transactions: {}", e),
+                error_code: None,
+            })
+        }
+    }
+}
+
+/// API endpoint: GET /api/address/{t_address}/timeline
+///
+/// Returns the address's events in block order, from the per-height change log.
+/// Only blocks indexed with `ENABLE_DELTA_LOG=true` have events.
+#[utoipa::path(
+    get,
+    path = "/api/address/{t_address}/timeline",
+    params(
+        ("t_address" = String, Path, description = "Twilight address to list events for"),
+        TimelineQuery
+    ),
+    responses(
+        (status = 200, description = "Successfully retrieved the address timeline", body = AddressTimelineResponse),
+        (status = 400, description = "from_height is above to_height", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "Stats"
+)]
+async fn get_address_timeline(path: web::Path<String>, query: web::Query<TimelineQuery>) -> impl Responder {
+    let t_address = path.into_inner();
+    if let (Some(from), Some(to)) = (query.from_height, query.to_height) {
+        if from > to {
+            return HttpResponse::BadRequest().json(ErrorResponse {
+                success: false,
+                error: format!("from_height {} is above to_height {}", from, to),
+                error_code: None,
+            });
+        }
+    }
+    let limit = page_limit(query.limit);
+    let offset = query.offset.unwrap_or(0) as i64;
+
+    match db::get_address_timeline(&t_address, query.from_height, query.to_height, limit, offset) {
+        Ok(rows) => {
+            let events: Vec<TimelineEvent> = rows
+                .into_iter()
+                .map(|r| TimelineEvent {
+                    height: r.block_height,
+                    timestamp: r.timestamp.map(|t| t.and_utc().to_rfc3339_opts(SecondsFormat::Secs, true)),
+                    event_type: timeline_event_type(&r.table_name).to_string(),
+                    amount: r.delta,
+                    detail: r.detail,
+                })
+                .collect();
+
+            HttpResponse::Ok().json(AddressTimelineResponse {
+                success: true,
+                t_address,
+                limit,
+                offset,
+                events,
+            })
+        }
+        Err(e) => {
+            error!("Failed to fetch timeline for {}: {:?}", t_address, e);
+            HttpResponse::InternalServerError().json(ErrorResponse {
+                success: false,
+                error: format!("Failed to fetch address timeline: {}", e),
+                error_code: None,
+            })
+        }
+    }
+}
+
+/// How long `/api/totals` serves a cached result; the sums scan whole tables.
+const TOTALS_CACHE_TTL: Duration = Duration::from_secs(5);
+
+lazy_static! {
+    static ref TOTALS_CACHE: Mutex<Option<(Instant, TotalsResponse)>> = Mutex::new(None);
+}
+
+fn totals_response(totals: db::GlobalTotals, refreshed_at: Option<NaiveDateTime>) -> TotalsResponse {
+    TotalsResponse {
+        success: true,
+        transaction_count: totals.transaction_count,
+        funds_moved: totals.funds_moved,
+        lit_minted_sats: totals.lit_minted_sats,
+        lit_burned_sats: totals.lit_burned_sats,
+        dark_minted_sats: totals.dark_minted_sats,
+        dark_burned_sats: totals.dark_burned_sats,
+        refreshed_at: refreshed_at.map(|t| t.and_utc().to_rfc3339_opts(SecondsFormat::Secs, true)),
+    }
+}
+
+fn fetch_totals() -> anyhow::Result<TotalsResponse> {
+    let (totals, refreshed_at) = db::get_global_totals()?;
+    Ok(totals_response(totals, refreshed_at))
+}
+
+/// Recomputes `global_totals` every `interval_secs` (starting now) while the server runs.
+pub async fn refresh_summary_periodically(interval_secs: u64) {
+    let mut ticker = actix_web::rt::time::interval(Duration::from_secs(interval_secs));
+    loop {
+        ticker.tick().await;
+        match web::block(db::refresh_global_totals).await {
+            Ok(Ok(_)) => debug!("Refreshed global_totals"),
+            Ok(Err(e)) => warn!("Failed to refresh global_totals: {:?}", e),
+            Err(e) => warn!("Failed to refresh global_totals: {}", e),
+        }
+    }
+}
+
+/// API endpoint: GET /api/totals
+/// Returns network-wide totals, cached for a few seconds. With `SUMMARY_REFRESH_SECS`
+/// set they come from the `global_totals` snapshot rather than a scan.
+#[utoipa::path(
+    get,
+    path = "/api/totals",
+    responses(
+        (status = 200, description = "Successfully retrieved network totals", body = TotalsResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "Stats"
+)]
+async fn get_totals() -> impl Responder {
+    let mut cache = TOTALS_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((fetched_at, totals)) = cache.as_ref() {
+        if fetched_at.elapsed() < TOTALS_CACHE_TTL {
+            return HttpResponse::Ok().json(totals);
+        }
+    }
+
+    match fetch_totals() {
+        Ok(totals) => {
+            *cache = Some((Instant::now(), totals.clone()));
+            HttpResponse::Ok().json(totals)
+        }
+        Err(e) => {
+            error!("Failed to fetch totals: {:?}", e);
+            HttpResponse::InternalServerError().json(ErrorResponse {
+                success: false,
+                error: format!("Failed to fetch totals: {}", e),
+                error_code: None,
+            })
+        }
+    }
+}
+
+/// How long `/api/stats/summary` reuses the chain height before asking the node again.
+const CHAIN_HEIGHT_CACHE_TTL: Duration = Duration::from_secs(10);
+
+lazy_static! {
+    static ref CHAIN_HEIGHT_CACHE: Mutex<Option<(Instant, u64)>> = Mutex::new(None);
+}
+
+/// Latest chain height: the indexer's copy while it is keeping it fresh, otherwise
+/// (indexer disabled or busy catching up) fetched from the node at most once per
+/// `CHAIN_HEIGHT_CACHE_TTL`.
+async fn cached_chain_height() -> Option<u64> {
+    if let Some(height) = pubsub_chain::get_cached_latest_height() {
+        return Some(height);
+    }
+    if let Some((fetched_at, height)) = *CHAIN_HEIGHT_CACHE.lock().unwrap_or_else(|e| e.into_inner()) {
+        if fetched_at.elapsed() < CHAIN_HEIGHT_CACHE_TTL {
+            return Some(height);
+        }
+    }
+
+    // The chain client is blocking, so keep it off the async workers
+    let fetched = match web::block(|| pubsub_chain::chain().latest_height()).await {
+        Ok(fetched) => fetched,
+        Err(e) => Err(BlockFetchError::Transient(e.to_string())),
+    };
+    match fetched {
+        Ok(height) => {
+            *CHAIN_HEIGHT_CACHE.lock().unwrap_or_else(|e| e.into_inner()) = Some((Instant::now(), height));
+            Some(height)
+        }
+        Err(e) => {
+            warn!("Failed to fetch latest chain height: {}", e);
+            None
+        }
+    }
+}
+
+/// API endpoint: GET /api/stats/summary
+/// Returns the indexer's sync status alongside global counts
+#[utoipa::path(
+    get,
+    path = "/api/stats/summary",
+    responses(
+        (status = 200, description = "Successfully retrieved the summary", body = StatsSummaryResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "Stats"
+)]
+async fn get_stats_summary() -> impl Responder {
+    let summary = match db::get_index_summary() {
+        Ok(summary) => summary,
+        Err(e) => {
+            error!("Failed to fetch stats summary: {:?}", e);
+            return HttpResponse::InternalServerError().json(ErrorResponse {
+                success: false,
+                error: format!("Failed to fetch stats summary: {}", e),
+                error_code: None,
+            });
+        }
+    };
+    let chain_latest_height = cached_chain_height().await;
+
+    HttpResponse::Ok().json(StatsSummaryResponse {
+        success: true,
+        indexed_height: summary.indexed_height,
+        chain_latest_height,
+        blocks_behind: chain_latest_height
+            .zip(summary.indexed_height)
+            .map(|(latest, indexed)| latest.saturating_sub(indexed)),
+        transaction_count: summary.transaction_count,
+        address_count: summary.address_count,
+        lit_minted_sats: summary.lit_minted_sats,
+        dark_minted_sats: summary.dark_minted_sats,
+        totals_refreshed_at: summary
+            .totals_refreshed_at
+            .map(|t| t.and_utc().to_rfc3339_opts(SecondsFormat::Secs, true)),
+    })
+}
+
+/// API endpoint: GET /api/addresses?sort=&order=&limit=&offset=
+/// Returns addresses ranked by transaction count or funds moved
+#[utoipa::path(
+    get,
+    path = "/api/addresses",
+    params(AddressesQuery),
+    responses(
+        (status = 200, description = "Successfully retrieved addresses", body = AddressesResponse),
+        (status = 400, description = "Invalid sort or order", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "Stats"
+)]
+async fn get_addresses(query: web::Query<AddressesQuery>) -> impl Responder {
+    let (sort, descending) = match parse_address_sort(query.sort.as_deref(), query.order.as_deref()) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(ErrorResponse {
+                success: false,
+                error: e,
+                error_code: None,
+            })
+        }
+    };
+    let limit = page_limit(query.limit);
+    let offset = query.offset.unwrap_or(0) as i64;
+
+    match db::list_addresses(sort, descending, limit, offset) {
+        Ok((rows, total)) => {
+            let addresses: Vec<AddressActivityData> = rows
+                .into_iter()
+                .map(|r| AddressActivityData {
+                    t_address: r.t_address,
+                    tx_count: r.tx_count,
+                    funds_moved: r.funds_moved,
+                })
+                .collect();
+
+            HttpResponse::Ok().json(AddressesResponse {
+                success: true,
+                total,
+                limit,
+                offset,
+                addresses,
+            })
+        }
+        Err(e) => {
+            error!("Failed to list addresses: {:?}", e);
+            HttpResponse::InternalServerError().json(ErrorResponse {
+                success: false,
+                error: format!("Failed to list addresses: {}", e),
+                error_code: None,
+            })
+        }
+    }
+}
+
+/// Shared response for the top-holders endpoints
+fn top_minted_response(what: &str, limit: i64, result: anyhow::Result<Vec<db::NetMinted>>) -> HttpResponse {
+    match result {
+        Ok(rows) => {
+            let addresses: Vec<NetMintedData> = rows.into_iter().map(NetMintedData::from).collect();
+
+            HttpResponse::Ok().json(TopMintedResponse {
+                success: true,
+                limit,
+                addresses,
+            })
+        }
+        Err(e) => {
+            error!("Failed to fetch top {}: {:?}", what, e);
+            HttpResponse::InternalServerError().json(ErrorResponse {
+                success: false,
+                error: format!("Failed to fetch top {}: {}", what, e),
+                error_code: None,
+            })
+        }
+    }
+}
+
+/// API endpoint: GET /api/top/lit-minted?limit=N
+/// Returns the addresses with the largest net BTC deposited (minted minus burned)
+#[utoipa::path(
+    get,
+    path = "/api/top/lit-minted",
+    params(TopQuery),
+    responses(
+        (status = 200, description = "Successfully retrieved top lit minted addresses", body = TopMintedResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "Stats"
+)]
+async fn get_top_lit_minted(query: web::Query<TopQuery>) -> impl Responder {
+    let limit = page_limit(query.limit);
+    top_minted_response("lit minted sats", limit, db::get_top_lit_minted(limit))
+}
+
+/// API endpoint: GET /api/top/dark-minted?limit=N
+/// Returns the addresses with the largest net funding-to-trading transfers (minted minus burned)
+#[utoipa::path(
+    get,
+    path = "/api/top/dark-minted",
+    params(TopQuery),
+    responses(
+        (status = 200, description = "Successfully retrieved top dark minted addresses", body = TopMintedResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "Stats"
+)]
+async fn get_top_dark_minted(query: web::Query<TopQuery>) -> impl Responder {
+    let limit = page_limit(query.limit);
+    top_minted_response("dark minted sats", limit, db::get_top_dark_minted(limit))
+}
+
+fn fetch_reserve_health(limit: i64) -> anyhow::Result<ReserveHealthResponse> {
+    let lit_minted_sats = db::get_total_lit_minted_sats()?;
+    let lit_burned_sats = db::get_total_lit_burned_sats()?;
+    let net_sats = lit_minted_sats.saturating_sub(lit_burned_sats);
+    let (overdrawn, overdrawn_count) = db::get_lit_overdrawn(limit)?;
+
+    Ok(ReserveHealthResponse {
+        success: true,
+        healthy: net_sats >= 0 && overdrawn_count == 0,
+        lit_minted_sats,
+        lit_burned_sats,
+        net_sats,
+        overdrawn_count,
+        overdrawn: overdrawn.into_iter().map(NetMintedData::from).collect(),
+    })
+}
+
+/// API endpoint: GET /api/reserve/health?limit=N
+///
+/// Checks BTC deposits (lit mints) against withdrawals (lit burns). An address
+/// that withdrew more than it deposited points at a decode bug or a missed
+/// block, unless its deposit predates the indexed range.
+#[utoipa::path(
+    get,
+    path = "/api/reserve/health",
+    params(TopQuery),
+    responses(
+        (status = 200, description = "Successfully checked the reserve totals", body = ReserveHealthResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "Stats"
+)]
+async fn get_reserve_health(query: web::Query<TopQuery>) -> impl Responder {
+    match fetch_reserve_health(page_limit(query.limit)) {
+        Ok(health) => {
+            if !health.healthy {
+                warn!(
+                    "Reserve check: net {} sats, {} address(es) withdrew more than they deposited",
+                    health.net_sats, health.overdrawn_count
+                );
+            }
+            HttpResponse::Ok().json(health)
+        }
+        Err(e) => {
+            error!("Failed to check reserve health: {:?}", e);
+            HttpResponse::InternalServerError().json(ErrorResponse {
+                success: false,
+                error: format!("Failed to check reserve health: {}", e),
+                error_code: None,
+            })
+        }
+    }
+}
+
+/// Rows fetched per query while streaming an export.
+const EXPORT_PAGE_ROWS: i64 = 1000;
+
+/// One CSV record, quoting fields that contain a comma, quote or line break.
+fn csv_line<S: AsRef<str>>(fields: &[S]) -> String {
+    let mut line = fields
+        .iter()
+        .map(|field| {
+            let field = field.as_ref();
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    line.push_str("\r\n");
+    line
+}
+
+/// API endpoint: GET /api/export/{table}.csv
+/// Streams a whole per-address table as CSV, a page at a time
+#[utoipa::path(
+    get,
+    path = "/api/export/{table}.csv",
+    params(
+        ("table" = String, Path, description = "One of transactions, funds_moved, dark_burned_sats, dark_minted_sats, lit_minted_sats, lit_burned_sats, addr_mappings, gas_used_nyks, fees_paid, ibc_transfers")
+    ),
+    responses(
+        (status = 200, description = "The table as CSV, with a header row", body = String, content_type = "text/csv"),
+        (status = 404, description = "Table is not exportable", body = ErrorResponse)
+    ),
+    tag = "Stats"
+)]
+async fn export_table(path: web::Path<String>) -> impl Responder {
+    let Some(table) = db::ExportTable::from_name(&path.into_inner()) else {
+        return HttpResponse::NotFound().json(ErrorResponse {
+            success: false,
+            error: "Table is not exportable".to_string(),
+            error_code: Some("NOT_FOUND".to_string()),
+        });
+    };
+
+    let header_row = stream::once(future::ready(Ok::<_, actix_web::Error>(web::Bytes::from(csv_line(table.columns())))));
+    // The next offset to read, or None once a short page shows the table is done
+    let rows = stream::unfold(Some(0), move |offset| async move {
+        let offset = offset?;
+        let page = match web::block(move || db::export_page(table, EXPORT_PAGE_ROWS, offset)).await {
+            Ok(page) => page,
+            Err(e) => Err(e.into()),
+        };
+        match page {
+            Ok(page) => {
+                let next = (page.len() as i64 == EXPORT_PAGE_ROWS).then_some(offset + EXPORT_PAGE_ROWS);
+                let chunk: String = page.iter().map(|row| csv_line(row)).collect();
+                Some((Ok(web::Bytes::from(chunk)), next))
+            }
+            Err(e) => {
+                // Headers are already sent, so all we can do is cut the download short
+                error!("Failed to export {} at offset {}: {:?}", table.name(), offset, e);
+                Some((Err(actix_web::error::ErrorInternalServerError(e)), None))
+            }
+        }
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .insert_header((header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}.csv\"", table.name())))
+        .streaming(header_row.chain(rows))
+}
+
+/// API endpoint: GET /api/transactions?block_height=N&limit=&offset=
+/// Returns the QuisQuis transactions stored for a block
+#[utoipa::path(
+    get,
+    path = "/api/transactions",
+    params(QQTransactionsQuery),
+    responses(
+        (status = 200, description = "Successfully retrieved QuisQuis transactions", body = QQTransactionsResponse),
+        (status = 400, description = "Missing or invalid query parameters"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "QuisQuis Transactions"
+)]
+async fn get_qq_transactions(query: web::Query<QQTransactionsQuery>) -> impl Responder {
+    let limit = page_limit(query.limit);
+    let offset = query.offset.unwrap_or(0) as i64;
+
+    let result = db::get_qq_txs_by_height(query.block_height as i64, limit, offset)
+        .and_then(|records| Ok((records, db::get_block_time(query.block_height)?)));
+    match result {
+        Ok((records, block_time)) => {
+            let transactions: Vec<QQTransactionData> = records
+                .into_iter()
+                .map(|r| QQTransactionData {
+                    id: r.id,
+                    block_height: r.block_height,
+                    tx_type: r.tx_type,
+                    tx_json: serde_json::from_str(&r.tx_json).unwrap_or(Value::String(r.tx_json)),
+                })
+                .collect();
+
+            HttpResponse::Ok().json(QQTransactionsResponse {
+                success: true,
+                block_height: query.block_height,
+                block_time: block_time.map(|t| t.and_utc().to_rfc3339_opts(SecondsFormat::Secs, true)),
+                limit,
+                offset,
+                transactions,
+            })
+        }
+        Err(e) => {
+            error!("Failed to fetch QuisQuis transactions: {:?}", e);
+            HttpResponse::InternalServerError().json(ErrorResponse {
+                success: false,
+                error: format!("Failed to fetch QuisQuis transactions: {}", e),
+                error_code: None,
+            })
+        }
+    }
+}
+
+/// API endpoint: GET /api/transactions/{id}
+/// Returns one stored QuisQuis transaction by row id
+///
+/// Only numeric ids are routed here; `/api/transactions/{t_address}` handles
+/// Twilight addresses.
+#[utoipa::path(
+    get,
+    path = "/api/transactions/{id}",
+    params(
+        ("id" = i32, Path, description = "Row id from the `/api/transactions` list")
+    ),
+    responses(
+        (status = 200, description = "Successfully retrieved the QuisQuis transaction", body = QQTransactionDetailResponse),
+        (status = 404, description = "No transaction with this id", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "QuisQuis Transactions"
+)]
+async fn get_qq_transaction(path: web::Path<i32>) -> impl Responder {
+    let tx_id = path.into_inner();
+
+    match db::get_qq_tx_by_id(tx_id) {
+        Ok(Some(r)) => HttpResponse::Ok().json(QQTransactionDetailResponse {
+            success: true,
+            id: r.id,
+            tx_hash: r.tx_hash,
+            block_height: r.block_height,
+            tx_type: r.tx_type,
+            created_at: r.created_at.and_utc().to_rfc3339_opts(SecondsFormat::Secs, true),
+            tx_json: serde_json::from_str(&r.tx_json).unwrap_or(Value::String(r.tx_json)),
+        }),
+        Ok(None) => HttpResponse::NotFound().json(ErrorResponse {
+            success: false,
+            error: format!("No QuisQuis transaction with id {}", tx_id),
+            error_code: Some("NOT_FOUND".to_string()),
+        }),
+        Err(e) => {
+            error!("Failed to fetch QuisQuis transaction {}: {:?}", tx_id, e);
+            HttpResponse::InternalServerError().json(ErrorResponse {
+                success: false,
+                error: format!("Failed to fetch QuisQuis transaction: {}", e),
+                error_code: None,
+            })
+        }
+    }
+}
+
+// ---------- Admin: reindex jobs ----------
+
+lazy_static! {
+    /// Reindex jobs started since the process came up, by id. Kept in memory only.
+    static ref REINDEX_JOBS: Mutex<HashMap<u64, ReindexJobResponse>> = Mutex::new(HashMap::new());
+}
+
+static NEXT_REINDEX_JOB_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Most heights one reindex job may cover.
+const MAX_REINDEX_BLOCKS: u64 = 10_000;
+
+/// Request payload for reindexing a height range
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReindexRequest {
+    /// First height to reindex
+    pub from: u64,
+    /// Last height to reindex (inclusive)
+    pub to: u64,
+}
+
+/// A started reindex job
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReindexStartedResponse {
+    pub success: bool,
+    pub job_id: u64,
+}
+
+/// Status of a reindex job
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReindexJobResponse {
+    pub success: bool,
+    pub job_id: u64,
+    pub from: u64,
+    pub to: u64,
+    /// `running`, `finished` or `failed`
+    pub status: String,
+    /// Blocks indexed, once the job has finished
+    pub blocks_indexed: Option<u64>,
+}
+
+/// Compares without returning early, so the token can't be guessed byte by byte from timings.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Whether an `Authorization` header value carries `Bearer <expected>`.
+fn bearer_token_matches(header: Option<&str>, expected: &str) -> bool {
+    header
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .is_some_and(|token| constant_time_eq(token.trim().as_bytes(), expected.as_bytes()))
+}
+
+/// `None` if the request may use the admin endpoints, otherwise the response to send.
+/// The endpoints are disabled unless `ADMIN_TOKEN` is set in the app's `Config`.
+fn check_admin(req: &HttpRequest) -> Option<HttpResponse> {
+    let admin_token = req.app_data::<web::Data<Config>>().and_then(|config| config.admin_token.clone());
+    let Some(expected) = admin_token.as_deref() else {
+        return Some(HttpResponse::Forbidden().json(ErrorResponse {
+            success: false,
+            error: "Admin endpoints are disabled; set ADMIN_TOKEN to enable them".to_string(),
+            error_code: None,
+        }));
+    };
+    let header = req.headers().get(header::AUTHORIZATION).and_then(|v| v.to_str().ok());
+    if bearer_token_matches(header, expected) {
+        None
+    } else {
+        Some(HttpResponse::Unauthorized().json(ErrorResponse {
+            success: false,
+            error: "Missing or invalid admin bearer token".to_string(),
+            error_code: None,
+        }))
+    }
+}
+
+fn set_reindex_job(job: ReindexJobResponse) {
+    REINDEX_JOBS.lock().unwrap_or_else(|e| e.into_inner()).insert(job.job_id, job);
+}
+
+/// Why `from..=to` can't be reindexed, if it can't.
+fn reindex_range_error(from: u64, to: u64) -> Option<String> {
+    if from == 0 || from > to {
+        return Some(format!("Invalid range {}..={}: need 1 <= from <= to", from, to));
+    }
+    if to - from >= MAX_REINDEX_BLOCKS {
+        return Some(format!(
+            "Range {}..={} covers {} heights; at most {} per job",
+            from,
+            to,
+            to - from + 1,
+            MAX_REINDEX_BLOCKS
+        ));
+    }
+    None
+}
+
+/// Registers a running job for `from..=to`, or returns the id of the job that
+/// is still running: only one job runs at a time.
+fn register_reindex_job(from: u64, to: u64) -> Result<ReindexJobResponse, u64> {
+    let mut jobs = REINDEX_JOBS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(running) = jobs.values().find(|job| job.status == "running") {
+        return Err(running.job_id);
+    }
+    let job = ReindexJobResponse {
+        success: true,
+        job_id: NEXT_REINDEX_JOB_ID.fetch_add(1, Ordering::SeqCst),
+        from,
+        to,
+        status: "running".to_string(),
+        blocks_indexed: None,
+    };
+    jobs.insert(job.job_id, job.clone());
+    Ok(job)
+}
+
+/// API endpoint: POST /api/admin/reindex
+///
+/// Starts reindexing `from..=to` on the blocking pool (the same path as the
+/// `backfill` command) and returns a job id straight away. Only heights that
+/// were never indexed are applied; a range that is fully indexed, larger than
+/// `MAX_REINDEX_BLOCKS`, or requested while another job runs is refused.
+/// Requires `Authorization: Bearer <ADMIN_TOKEN>`.
+#[utoipa::path(
+    post,
+    path = "/api/admin/reindex",
+    request_body = ReindexRequest,
+    responses(
+        (status = 202, description = "Reindex job started", body = ReindexStartedResponse),
+        (status = 400, description = "Invalid or too large height range", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorResponse),
+        (status = 403, description = "ADMIN_TOKEN is not set", body = ErrorResponse),
+        (status = 409, description = "Range already indexed, or another job is running", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    ),
+    tag = "Admin"
+)]
+async fn start_reindex(req: HttpRequest, body: web::Json<ReindexRequest>) -> impl Responder {
+    if let Some(denied) = check_admin(&req) {
+        return denied;
+    }
+    let ReindexRequest { from, to } = body.into_inner();
+    if let Some(error) = reindex_range_error(from, to) {
+        return HttpResponse::BadRequest().json(ErrorResponse { success: false, error, error_code: None });
+    }
+    match db::count_indexed_blocks(from, to) {
+        Ok(indexed) if indexed > to - from => {
+            return HttpResponse::Conflict().json(ErrorResponse {
+                success: false,
+                error: format!("Heights {} to {} are already indexed; a block is never applied twice", from, to),
+                error_code: None,
+            });
+        }
+        Ok(_) => {}
+        Err(e) => {
+            error!("Failed to check indexed heights {} to {}: {:?}", from, to, e);
+            return HttpResponse::InternalServerError().json(ErrorResponse {
+                success: false,
+                error: format!("Failed to check indexed heights: {}", e),
+                error_code: None,
+            });
+        }
+    }
+    let job = match register_reindex_job(from, to) {
+        Ok(job) => job,
+        Err(running) => {
+            return HttpResponse::Conflict().json(ErrorResponse {
+                success: false,
+                error: format!("Reindex job {} is still running", running),
+                error_code: None,
+            });
+        }
+    };
+    let job_id = job.job_id;
+    info!("Reindex job {} started for heights {} to {}", job_id, from, to);
+
+    // Detached: the handler returns while the range is indexed in the background
+    actix_web::rt::task::spawn_blocking(move || {
+        // A panic must not leave the job "running", which would block every later job
+        let outcome = std::panic::catch_unwind(|| pubsub_chain::backfill_range(from, to, &AtomicBool::new(false)));
+        let finished = match outcome {
+            Ok(indexed) => {
+                info!("Reindex job {} finished: {} blocks indexed", job_id, indexed);
+                ReindexJobResponse { status: "finished".to_string(), blocks_indexed: Some(indexed), ..job }
+            }
+            Err(_) => {
+                error!("Reindex job {} panicked", job_id);
+                ReindexJobResponse { status: "failed".to_string(), ..job }
+            }
+        };
+        set_reindex_job(finished);
+    });
+
+    HttpResponse::Accepted().json(ReindexStartedResponse { success: true, job_id })
+}
+
+/// API endpoint: GET /api/admin/reindex/{job_id}
+/// Returns the status of a reindex job
+#[utoipa::path(
+    get,
+    path = "/api/admin/reindex/{job_id}",
+    params(
+        ("job_id" = u64, Path, description = "Id returned by POST /api/admin/reindex")
+    ),
+    responses(
+        (status = 200, description = "Reindex job status", body = ReindexJobResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorResponse),
+        (status = 403, description = "ADMIN_TOKEN is not set", body = ErrorResponse),
+        (status = 404, description = "No job with this id", body = ErrorResponse)
+    ),
+    tag = "Admin"
+)]
+async fn get_reindex_job(req: HttpRequest, path: web::Path<u64>) -> impl Responder {
+    if let Some(denied) = check_admin(&req) {
+        return denied;
+    }
+    let job_id = path.into_inner();
+    let job = REINDEX_JOBS.lock().unwrap_or_else(|e| e.into_inner()).get(&job_id).cloned();
+    match job {
+        Some(job) => HttpResponse::Ok().json(job),
+        None => HttpResponse::NotFound().json(ErrorResponse {
+            success: false,
+            error: format!("No reindex job with id {}", job_id),
+            error_code: Some("NOT_FOUND".to_string()),
+        }),
+    }
+}
+
+/// Query parameters for listing skipped blocks
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SkippedBlocksQuery {
+    /// Page size (default 50, max 500)
+    pub limit: Option<u32>,
+    /// Number of rows to skip (default 0)
+    pub offset: Option<u32>,
+}
+
+/// A height the indexer moved past without indexing it
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SkippedBlockData {
+    pub height: i64,
+    /// Error from the last failed attempt
+    pub reason: String,
+    pub attempts: i32,
+    /// When the block was last tried (RFC 3339, UTC)
+    pub last_tried: String,
+}
+
+/// Skipped blocks, lowest height first
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SkippedBlocksResponse {
+    pub success: bool,
+    pub limit: i64,
+    pub offset: i64,
+    pub blocks: Vec<SkippedBlockData>,
+}
+
+/// API endpoint: GET /api/admin/skipped
+///
+/// Lists heights the indexer had to skip because the block couldn't be fetched
+/// or decoded. The indexer retries them in the background and removes each one
+/// once it has been indexed. Requires `Authorization: Bearer <ADMIN_TOKEN>`.
+#[utoipa::path(
+    get,
+    path = "/api/admin/skipped",
+    params(SkippedBlocksQuery),
+    responses(
+        (status = 200, description = "Skipped blocks", body = SkippedBlocksResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorResponse),
+        (status = 403, description = "ADMIN_TOKEN is not set", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "Admin"
+)]
+async fn get_skipped_blocks(req: HttpRequest, query: web::Query<SkippedBlocksQuery>) -> impl Responder {
+    if let Some(denied) = check_admin(&req) {
+        return denied;
+    }
+    let limit = page_limit(query.limit);
+    let offset = query.offset.unwrap_or(0) as i64;
+
+    match db::get_skipped_blocks(limit, offset) {
+        Ok(rows) => {
+            let blocks: Vec<SkippedBlockData> = rows
+                .into_iter()
+                .map(|r| SkippedBlockData {
+                    height: r.height,
+                    reason: r.reason,
+                    attempts: r.attempts,
+                    last_tried: r.last_tried.and_utc().to_rfc3339_opts(SecondsFormat::Secs, true),
+                })
+                .collect();
+
+            HttpResponse::Ok().json(SkippedBlocksResponse {
+                success: true,
+                limit,
+                offset,
+                blocks,
+            })
+        }
+        Err(e) => {
+            error!("Failed to list skipped blocks: {:?}", e);
+            HttpResponse::InternalServerError().json(ErrorResponse {
+                success: false,
+                error: format!("Failed to list skipped blocks: {}", e),
+                error_code: None,
+            })
+        }
+    }
+}
+
+/// API endpoint: POST /api/admin/refresh-summary
+///
+/// Recomputes `global_totals` now instead of waiting for the next
+/// `SUMMARY_REFRESH_SECS` tick, and returns the new totals. Requires
+/// `Authorization: Bearer <ADMIN_TOKEN>`.
+#[utoipa::path(
+    post,
+    path = "/api/admin/refresh-summary",
+    responses(
+        (status = 200, description = "Totals recomputed", body = TotalsResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorResponse),
+        (status = 403, description = "ADMIN_TOKEN is not set", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "Admin"
+)]
+async fn refresh_summary(req: HttpRequest) -> impl Responder {
+    if let Some(denied) = check_admin(&req) {
+        return denied;
+    }
+
+    let refreshed = match web::block(db::refresh_global_totals).await {
+        Ok(refreshed) => refreshed,
+        Err(e) => Err(e.into()),
+    };
+    match refreshed {
+        Ok((totals, refreshed_at)) => {
+            // Don't let /api/totals keep serving the pre-refresh numbers
+            *TOTALS_CACHE.lock().unwrap_or_else(|e| e.into_inner()) = None;
+            HttpResponse::Ok().json(totals_response(totals, Some(refreshed_at)))
+        }
+        Err(e) => {
+            error!("Failed to refresh global totals: {:?}", e);
+            HttpResponse::InternalServerError().json(ErrorResponse {
+                success: false,
+                error: format!("Failed to refresh global totals: {}", e),
+                error_code: None,
+            })
+        }
+    }
+}
+
+/// Health check endpoint
+#[utoipa::path(
+    get,
+    path = "/api/health",
+    responses(
+        (status = 200, description = "Service is healthy")
+    ),
+    tag = "Health"
+)]
+async fn health_check() -> impl Responder {
+    HttpResponse::Ok().json(serde_json::json!({
+        "status": "healthy",
+        "service": "twilight-indexer-api"
+    }))
+}
+
+/// Version endpoint: reports the crate version and the commit it was built from
+#[utoipa::path(
+    get,
+    path = "/api/version",
+    responses(
+        (status = 200, description = "Build information", body = VersionResponse)
+    ),
+    tag = "Health"
+)]
+async fn version() -> impl Responder {
+    HttpResponse::Ok().json(VersionResponse {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_sha: env!("GIT_SHA").to_string(),
+        build_time: env!("BUILD_TIME").to_string(),
+    })
+}
+
+/// Readiness endpoint: checks the database and reports the indexed height
+#[utoipa::path(
+    get,
+    path = "/api/health/ready",
+    responses(
+        (status = 200, description = "Database reachable", body = ReadinessResponse),
+        (status = 503, description = "Database unreachable", body = ReadinessResponse)
+    ),
+    tag = "Health"
+)]
+async fn readiness_check() -> impl Responder {
+    if let Err(e) = db::ping() {
+        error!("Readiness check failed: {:?}", e);
+        return HttpResponse::ServiceUnavailable().json(ReadinessResponse {
+            status: "degraded".to_string(),
+            db: "unreachable".to_string(),
+            indexed_height: None,
+            indexer: pubsub_chain::indexer_status().as_str().to_string(),
+        });
+    }
+
+    let indexed_height = db::get_last_indexed_height().unwrap_or_else(|e| {
+        error!("Failed to read indexed height: {:?}", e);
+        None
+    });
+    HttpResponse::Ok().json(ReadinessResponse {
+        status: "ready".to_string(),
+        db: "ok".to_string(),
+        indexed_height,
+        indexer: pubsub_chain::indexer_status().as_str().to_string(),
+    })
+}
+
+/// Prometheus metrics endpoint
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    responses(
+        (status = 200, description = "Metrics in the Prometheus text exposition format", body = String, content_type = "text/plain")
+    ),
+    tag = "Monitoring"
+)]
+async fn metrics_endpoint() -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(metrics::gather())
+}
+
+/// OpenAPI documentation
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        health_check,
+        readiness_check,
+        version,
+        metrics_endpoint,
+        get_transactions,
+        get_funds_moved,
+        get_dark_burned_sats,
+        get_dark_minted_sats,
+        get_lit_minted_sats,
+        get_btc_deposit_addresses,
+        get_reserves,
         get_lit_burned_sats,
+        get_sweep_events,
         get_q_addresses,
-        get_address_all_data
+        get_addr_mapping,
+        get_dark_balance,
+        search,
+        get_address_all_data,
+        get_address_stats,
+        get_address_timeline,
+        get_address_transactions,
+        get_totals,
+        get_stats_summary,
+        get_addresses,
+        get_top_lit_minted,
+        get_top_dark_minted,
+        get_reserve_health,
+        export_table,
+        get_qq_transactions,
+        get_qq_transaction,
+        get_block_txs,
+        ws_transactions,
+        start_reindex,
+        get_reindex_job,
+        get_skipped_blocks,
+        refresh_summary
     ),
     components(
         schemas(
@@ -968,16 +3260,57 @@ async fn health_check() -> impl Responder {
             DarkMintedSatsData,
             LitMintedSatsResponse,
             LitMintedSatsData,
+            BtcDepositAddressesResponse,
+            BtcDepositAddressData,
+            ReservesResponse,
+            ReserveData,
             LitBurnedSatsResponse,
             LitBurnedSatsData,
+            SweepEventsResponse,
+            SweepEventData,
             QAddressesResponse,
             QAddressData,
+            AddrMappingResponse,
+            AddrMappingData,
+            DarkBalanceResponse,
+            SearchResponse,
+            SearchResult,
+            SearchAddressResult,
+            SearchQAddressResult,
+            SearchBlockResult,
+            DecodeResponse,
+            DecodeTxStandardResponse,
             AddressAllDataResponse,
+            AddressStatsResponse,
+            DenomAmount,
+            AddressTimelineResponse,
+            TimelineEvent,
+            TotalsResponse,
+            StatsSummaryResponse,
+            AddressesResponse,
+            AddressActivityData,
+            TopMintedResponse,
+            NetMintedData,
+            ReserveHealthResponse,
+            QQTransactionsResponse,
+            QQTransactionData,
+            AddressTransactionsResponse,
+            QQTransactionDetailResponse,
+            BlockTxsResponse,
+            BlockTxData,
+            ReindexRequest,
+            ReindexStartedResponse,
+            ReindexJobResponse,
+            SkippedBlocksResponse,
+            SkippedBlockData,
+            ReadinessResponse,
+            VersionResponse,
             ErrorResponse
         )
     ),
     tags(
         (name = "Health", description = "Health check endpoints"),
+        (name = "Monitoring", description = "Prometheus metrics for indexer progress"),
         (name = "Transactions", description = "Returns transaction blocks for each Twilight address"),
         (name = "Funding to Funding", description = "Returns funds moved between funding accounts"),
         (name = "Funding to Trading", description = "Returns funds moved from funding to trading accounts"),
@@ -985,7 +3318,9 @@ async fn health_check() -> impl Responder {
         (name = "BTC Deposited", description = "Returns Btc Deposited to Twilight Reserves"),
         (name = "BTC Withdrawn", description = "Returns Btc Withdrawn from Twilight Reserves"),
         (name = "Twilight/qq mapping", description = "Address mappings between Twilight and quis quis accounts"),
-        (name = "Stats", description = "General stats for a given Twilight address")
+        (name = "Stats", description = "General stats for a given Twilight address, and network-wide totals"),
+        (name = "QuisQuis Transactions", description = "Decoded QuisQuis transactions stored by block"),
+        (name = "Admin", description = "Operator endpoints, enabled by ADMIN_TOKEN")
     ),
     info(
         title = "Twilight Indexer API",
@@ -1000,34 +3335,100 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/api")
             .route("/health", web::get().to(health_check))
+            .route("/health/ready", web::get().to(readiness_check))
+            .route("/version", web::get().to(version))
             .route("/decode-transaction", web::post().to(decode_transaction_endpoint))
+            .service(
+                web::resource("/decode-transactions")
+                    .app_data(web::JsonConfig::default().limit(DECODE_BATCH_BODY_LIMIT))
+                    .route(web::post().to(decode_transactions_endpoint)),
+            )
+            .route("/decode-tx-standard", web::post().to(decode_tx_standard_endpoint))
+            .route("/transactions", web::get().to(get_qq_transactions))
+            .route("/transactions/{id:\\d+}", web::get().to(get_qq_transaction))
             .route("/transactions/{t_address}", web::get().to(get_transactions))
             .route("/funding/{t_address}", web::get().to(get_funds_moved))
             .route("/exchange-withdrawal/{t_address}", web::get().to(get_dark_burned_sats))
             .route("/exchange-deposit/{t_address}", web::get().to(get_dark_minted_sats))
             .route("/btc-deposit/{t_address}", web::get().to(get_lit_minted_sats))
+            .route("/btc-deposit-addresses", web::get().to(get_btc_deposit_addresses))
+            .route("/reserves", web::get().to(get_reserves))
+            .route("/sweeps/{reserve_id}/events", web::get().to(get_sweep_events))
             .route("/btc-withdrawal/{t_address}", web::get().to(get_lit_burned_sats))
             .route("/qq-account/{t_address}", web::get().to(get_q_addresses))
+            .route("/addr-mapping", web::get().to(get_addr_mapping))
+            .route("/dark-balance", web::get().to(get_dark_balance))
+            .route("/search", web::get().to(search))
             .route("/address/{t_address}/all", web::get().to(get_address_all_data))
-    );
+            .route("/address/{t_address}/stats", web::get().to(get_address_stats))
+            .route("/address/{t_address}/timeline", web::get().to(get_address_timeline))
+            .route("/address/{t_address}/transactions", web::get().to(get_address_transactions))
+            .route("/totals", web::get().to(get_totals))
+            .route("/stats/summary", web::get().to(get_stats_summary))
+            .route("/block/{height}/txs", web::get().to(get_block_txs))
+            .route("/ws/transactions", web::get().to(ws_transactions))
+            .route("/addresses", web::get().to(get_addresses))
+            .route("/admin/reindex", web::post().to(start_reindex))
+            .route("/admin/reindex/{job_id}", web::get().to(get_reindex_job))
+            .route("/admin/skipped", web::get().to(get_skipped_blocks))
+            .route("/admin/refresh-summary", web::post().to(refresh_summary))
+            .route("/top/lit-minted", web::get().to(get_top_lit_minted))
+            .route("/top/dark-minted", web::get().to(get_top_dark_minted))
+            .route("/reserve/health", web::get().to(get_reserve_health))
+            .route("/export/{table}.csv", web::get().to(export_table))
+    )
+    .route("/metrics", web::get().to(metrics_endpoint));
+}
+
+/// CORS limited to `origins` (GET/POST with the headers the API reads), or any
+/// origin, method and header when no list is configured.
+fn build_cors(origins: Option<&[String]>) -> Cors {
+    let Some(origins) = origins else {
+        return Cors::permissive().max_age(3600);
+    };
+    origins
+        .iter()
+        .fold(Cors::default(), |cors, origin| cors.allowed_origin(origin))
+        .allowed_methods(["GET", "POST"])
+        .allowed_headers([header::AUTHORIZATION, header::ACCEPT, header::CONTENT_TYPE])
+        .max_age(3600)
 }
 
-/// Start the API server
-pub async fn start_api_server(host: &str, port: u16) -> std::io::Result<()> {
+/// Start the API server on `config.api_host:config.api_port`
+pub async fn start_api_server(config: &Config) -> std::io::Result<()> {
+    let (host, port) = (config.api_host.as_str(), config.api_port);
     let openapi = ApiDoc::openapi();
 
-    println!("🚀 Starting API server at http://{}:{}", host, port);
-    println!("📚 Swagger UI available at http://{}:{}/swagger-ui/", host, port);
+    info!("Starting API server at http://{}:{}", host, port);
+    info!("Swagger UI available at http://{}:{}/swagger-ui/", host, port);
 
+    match config.api_allowed_origins.as_deref() {
+        Some(origins) => info!("CORS allows origins: {}", origins.join(", ")),
+        None => warn!("API_ALLOWED_ORIGINS is not set, CORS allows any origin"),
+    }
+
+    // One limiter shared by every worker, so a client's budget doesn't scale with the worker count
+    let limiter = rate_limit::RateLimiter::from_config(config).map(web::Data::new);
+    match &limiter {
+        Some(_) => info!(
+            "Rate limiting API clients to {} requests/s (burst {})",
+            config.rate_limit_rps, config.rate_limit_burst
+        ),
+        None => info!("API rate limiting is off (RATE_LIMIT_RPS=0)"),
+    }
+
+    let app_config = web::Data::new(config.clone());
     HttpServer::new(move || {
-        let cors = Cors::default()
-            .allow_any_origin()
-            .allow_any_method()
-            .allow_any_header()
-            .supports_credentials()
-            .max_age(3600);
-
-        App::new()
+        let cors = build_cors(app_config.api_allowed_origins.as_deref());
+
+        let mut app = App::new()
+            .app_data(app_config.clone())
+            .app_data(decode_json_config(app_config.decode_max_input_len));
+        if let Some(limiter) = &limiter {
+            app = app.app_data(limiter.clone());
+        }
+        app
+            .wrap(middleware::from_fn(rate_limit::limit_by_ip))
             .wrap(cors)
             .service(
                 SwaggerUi::new("/swagger-ui/{_:.*}")
@@ -1038,4 +3439,227 @@ pub async fn start_api_server(host: &str, port: u16) -> std::io::Result<()> {
     .bind((host, port))?
     .run()
     .await
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn address_sort_defaults_and_rejects_unknown_values() {
+        assert_eq!(parse_address_sort(None, None), Ok((db::AddressSort::TxCount, true)));
+        assert_eq!(parse_address_sort(Some("funds_moved"), Some("asc")), Ok((db::AddressSort::FundsMoved, false)));
+        assert!(parse_address_sort(Some("t_address; DROP TABLE transactions"), None).is_err());
+        assert!(parse_address_sort(None, Some("sideways")).is_err());
+    }
+
+    #[test]
+    fn block_txs_keep_decode_errors_inline() {
+        use base64::{engine::general_purpose::STANDARD as B64, Engine as _};
+        use cosmos_sdk_proto::cosmos::tx::v1beta1::{AuthInfo, TxBody, TxRaw};
+        use prost::Message;
+
+        let body = TxBody { memo: "hello".to_string(), ..Default::default() };
+        let tx_raw = TxRaw {
+            body_bytes: body.encode_to_vec(),
+            auth_info_bytes: AuthInfo::default().encode_to_vec(),
+            signatures: vec![],
+        };
+        let txs = decode_block_txs(&["not base64!".to_string(), B64.encode(tx_raw.encode_to_vec())]);
+
+        assert_eq!(txs.len(), 2);
+        assert!(txs[0].tx.is_none() && txs[0].error.is_some());
+        assert_eq!(txs[1].index, 1);
+        assert_eq!(txs[1].tx.as_ref().unwrap()["memo"], "hello");
+        assert!(txs[1].error.is_none());
+    }
+
+    #[test]
+    fn reindex_ranges_are_bounded() {
+        assert!(reindex_range_error(0, 10).is_some());
+        assert!(reindex_range_error(20, 10).is_some());
+        assert!(reindex_range_error(1, MAX_REINDEX_BLOCKS).is_none());
+        assert!(reindex_range_error(1, MAX_REINDEX_BLOCKS + 1).is_some());
+    }
+
+    #[test]
+    fn one_reindex_job_runs_at_a_time() {
+        let job = register_reindex_job(10, 20).unwrap();
+        assert_eq!(register_reindex_job(30, 40).unwrap_err(), job.job_id);
+
+        set_reindex_job(ReindexJobResponse { status: "finished".to_string(), ..job });
+        let next = register_reindex_job(30, 40).unwrap();
+        set_reindex_job(ReindexJobResponse { status: "finished".to_string(), ..next });
+    }
+
+    #[test]
+    fn admin_token_must_match_exactly() {
+        assert!(bearer_token_matches(Some("Bearer s3cret"), "s3cret"));
+        assert!(!bearer_token_matches(Some("Bearer s3cre"), "s3cret"));
+        assert!(!bearer_token_matches(Some("Bearer s3cret!"), "s3cret"));
+        assert!(!bearer_token_matches(Some("s3cret"), "s3cret"));
+        assert!(!bearer_token_matches(Some("Basic s3cret"), "s3cret"));
+        assert!(!bearer_token_matches(None, "s3cret"));
+    }
+
+    #[actix_web::test]
+    async fn cors_only_allows_listed_origins() {
+        use actix_web::http::StatusCode;
+        use actix_web::test as actix_test;
+
+        let origins = vec!["https://app.example".to_string()];
+        let app = actix_test::init_service(
+            App::new()
+                .wrap(build_cors(Some(&origins)))
+                .route("/api/totals", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        let get_from = |origin: &str| {
+            actix_test::TestRequest::get()
+                .uri("/api/totals")
+                .insert_header((header::ORIGIN, origin))
+                .to_request()
+        };
+
+        let allowed = actix_test::call_service(&app, get_from("https://app.example")).await;
+        assert_eq!(allowed.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(), "https://app.example");
+
+        // Not rejected server-side, but without the header the browser blocks the response
+        let denied = actix_test::call_service(&app, get_from("https://evil.example")).await;
+        assert!(!denied.headers().contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+
+        let preflight = actix_test::TestRequest::default()
+            .method(actix_web::http::Method::OPTIONS)
+            .uri("/api/totals")
+            .insert_header((header::ORIGIN, "https://app.example"))
+            .insert_header((header::ACCESS_CONTROL_REQUEST_METHOD, "DELETE"))
+            .to_request();
+        assert_eq!(actix_test::call_service(&app, preflight).await.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn timeline_event_types_are_named_for_the_frontend() {
+        assert_eq!(timeline_event_type("lit_minted_sats"), "lit_mint");
+        assert_eq!(timeline_event_type("funds_moved"), "send");
+        assert_eq!(timeline_event_type("dark_burned_sats"), "dark_burn");
+        assert_eq!(timeline_event_type("some_future_table"), "some_future_table");
+    }
+
+    #[test]
+    fn csv_lines_quote_only_when_needed() {
+        assert_eq!(csv_line(&["twilight1abc", "42"]), "twilight1abc,42\r\n");
+        assert_eq!(csv_line(&["a,b", "say \"hi\"", "x\ny"]), "\"a,b\",\"say \"\"hi\"\"\",\"x\ny\"\r\n");
+    }
+
+    #[actix_web::test]
+    async fn export_rejects_tables_outside_the_allowlist() {
+        use actix_web::http::StatusCode;
+        use actix_web::test as actix_test;
+
+        let app = actix_test::init_service(App::new().configure(configure_routes)).await;
+        let request = actix_test::TestRequest::get().uri("/api/export/indexer_state.csv").to_request();
+        assert_eq!(actix_test::call_service(&app, request).await.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn decode_batch_keeps_failures_per_item_and_caps_the_size() {
+        use actix_web::http::StatusCode;
+        use actix_web::test as actix_test;
+
+        let app = actix_test::init_service(App::new().configure(configure_routes)).await;
+        let batch = |items: Vec<&str>| {
+            let items: Vec<Value> = items.into_iter().map(|tx| serde_json::json!({ "tx_byte_code": tx })).collect();
+            actix_test::TestRequest::post()
+                .uri("/api/decode-transactions")
+                .set_json(serde_json::json!({ "items": items }))
+                .to_request()
+        };
+
+        let body: Value = actix_test::call_and_read_body_json(&app, batch(vec!["not hex!", "0x00"])).await;
+        let results = body["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["success"], false);
+        assert_eq!(results[0]["error_code"], "INVALID_HEX");
+        assert_eq!(results[1]["success"], false);
+
+        let too_many = batch(vec!["0x00"; MAX_DECODE_BATCH + 1]);
+        assert_eq!(actix_test::call_service(&app, too_many).await.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[actix_web::test]
+    async fn decode_rejects_oversized_input_before_decoding() {
+        use actix_web::http::StatusCode;
+        use actix_web::test as actix_test;
+
+        let decode = |tx_byte_code: String| {
+            actix_test::TestRequest::post()
+                .uri("/api/decode-transaction")
+                .set_json(serde_json::json!({ "tx_byte_code": tx_byte_code }))
+                .to_request()
+        };
+
+        let app = actix_test::init_service(App::new().configure(configure_routes)).await;
+        let too_long = decode("ab".repeat(DEFAULT_DECODE_MAX_INPUT_LEN / 2 + 1));
+        let response = actix_test::call_service(&app, too_long).await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body: Value = actix_test::read_body_json(response).await;
+        assert_eq!(body["success"], false);
+
+        // The body limit refuses the request before the length check sees it
+        let app = actix_test::init_service(App::new().app_data(decode_json_config(16)).configure(configure_routes)).await;
+        let oversized_body = decode("ab".repeat(DECODE_BODY_OVERHEAD));
+        assert_eq!(actix_test::call_service(&app, oversized_body).await.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let small = decode("0x00".to_string());
+        assert_eq!(actix_test::call_service(&app, small).await.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn search_guesses_from_the_shape_of_the_query() {
+        let q_address = "0c".repeat(33);
+        assert_eq!(classify_search("415150"), SearchGuess::Height(415150));
+        assert_eq!(classify_search("99999999999999999999999"), SearchGuess::Unknown);
+        assert_eq!(classify_search("twilight1qyqszqgpqyqszqgpqyqszqgpqyqszqgpjnp7du"), SearchGuess::TAddress);
+        assert_eq!(classify_search(&q_address), SearchGuess::Hex);
+        assert_eq!(classify_search(&format!("0x{}", q_address)), SearchGuess::Hex);
+        assert_eq!(classify_search("CpIBCo8BChwvY29zbW9zLmJhbmsu"), SearchGuess::Base64);
+        assert_eq!(classify_search("hello"), SearchGuess::Unknown);
+        assert_eq!(classify_search(""), SearchGuess::Unknown);
+    }
+
+    #[actix_web::test]
+    async fn btc_deposit_addresses_rejects_a_non_twilight_address() {
+        use actix_web::http::StatusCode;
+        use actix_web::test as actix_test;
+
+        let app = actix_test::init_service(App::new().configure(configure_routes)).await;
+        let request = actix_test::TestRequest::get()
+            .uri("/api/btc-deposit-addresses?twilight_address=bc1qdeposit")
+            .to_request();
+        let response = actix_test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn search_links_addresses_and_rejects_gibberish() {
+        use actix_web::http::StatusCode;
+        use actix_web::test as actix_test;
+
+        let app = actix_test::init_service(App::new().configure(configure_routes)).await;
+        let address = "twilight1qyqszqgpqyqszqgpqyqszqgpqyqszqgpjnp7du";
+        let request = actix_test::TestRequest::get().uri(&format!("/api/search?q={}", address)).to_request();
+        let body: Value = actix_test::call_and_read_body_json(&app, request).await;
+        assert_eq!(body["kind"], "address");
+        assert_eq!(body["result"]["stats_url"], format!("/api/address/{}/stats", address));
+
+        let request = actix_test::TestRequest::get().uri("/api/search?q=what%3F").to_request();
+        assert_eq!(actix_test::call_service(&app, request).await.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn page_limit_defaults_and_clamps() {
+        assert_eq!(page_limit(None), 50);
+        assert_eq!(page_limit(Some(10)), 10);
+        assert_eq!(page_limit(Some(0)), 1);
+        assert_eq!(page_limit(Some(10_000)), 500);
+    }
+}
@@ -2,13 +2,19 @@
 //!
 //! This module provides Rust structs for deserializing block and transaction data from
 //! Cosmos-based blockchains, as well as helpers for extracting and working with this data.
-use lazy_static::lazy_static;
+//! Blocks are fetched through a `BlockSource`; `ChainClient` is the one that talks to
+//! the node.
+use chrono::{DateTime, NaiveDateTime};
 use serde_derive::Deserialize;
 use serde_derive::Serialize;
 use serde_json::Value;
-lazy_static! {
-    pub static ref BLOCK_HEIGHT_FILE: String =
-        std::env::var("BLOCK_HEIGHT_FILE").unwrap_or_else(|_| "height.txt".to_string());
+/// `template` (with `{height}` substituted) joined onto `base` with a single slash.
+fn block_url(base: &str, template: &str, height: &str) -> String {
+    format!(
+        "{}/{}",
+        base.trim_end_matches('/'),
+        template.trim_start_matches('/').replace("{height}", height)
+    )
 }
 
 
@@ -22,6 +28,40 @@ pub struct BlockError {
     pub details: Vec<String>,
 }
 
+/// Why a block couldn't be fetched for a given height.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockFetchError {
+    /// The node hasn't reached this height yet (gRPC code 3); try again after the next poll.
+    HeightNotYetAvailable,
+    /// The node doesn't have this block (gRPC code 5 / HTTP 404); skip it.
+    NotFound,
+    /// Network or node-side failure that outlasted the request retries.
+    Transient(String),
+    /// The response was neither a block nor a recognisable error.
+    Decode(String),
+}
+
+impl fmt::Display for BlockFetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockFetchError::HeightNotYetAvailable => write!(f, "height not yet available"),
+            BlockFetchError::NotFound => write!(f, "block not found"),
+            BlockFetchError::Transient(msg) => write!(f, "transient error: {}", msg),
+            BlockFetchError::Decode(msg) => write!(f, "decode error: {}", msg),
+        }
+    }
+}
+
+impl From<BlockError> for BlockFetchError {
+    fn from(block_error: BlockError) -> Self {
+        match block_error.code {
+            3 => BlockFetchError::HeightNotYetAvailable,
+            5 => BlockFetchError::NotFound,
+            code => BlockFetchError::Transient(format!("code {}: {}", code, block_error.message)),
+        }
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Version {
@@ -212,84 +252,22 @@ impl BlockRaw {
     pub fn get_block_height(&mut self) -> u64 {
         self.block.header.height
     }
-    /// Retrieves the latest block height from the chain
-    pub fn get_latest_block_height() -> Result<u64, String> {
-        let url = format!(
-            "{}/cosmos/base/tendermint/v1beta1/blocks/latest",
-            *NYKS_BLOCK_SUBSCRIBER_URL
-        );
-        // println!("url :{:?}", url);
-        match request_url(&url) {
-            Ok(block_data) => {
-                let mut block = match BlockRaw::decode(block_data) {
-                    Ok(block) => block,
-                    Err(arg) => {
-                        println!("Error: {:?}", arg);
-                        return Err(arg.to_string());
-                    }
-                };
-
-                Ok(block.get_block_height())
-            }
-            Err(arg) => Err(arg.to_string()),
-        }
-    }
-    /// Retrieves block data for a specific height
-    pub fn get_block_data_from_height(block_height: u64) -> Result<BlockRaw, String> {
-        let url = format!(
-            "{}/cosmos/base/tendermint/v1beta1/blocks/{}",
-            *NYKS_BLOCK_SUBSCRIBER_URL, block_height,
-        );
-        match request_url(&url) {
-            Ok(block_data) => match BlockRaw::decode(block_data) {
-                Ok(block) => Ok(block),
-                Err(arg) => Err(arg.to_string()),
-            },
-            Err(arg) => Err(arg.to_string()),
-        }
+    /// Header time in UTC, or `None` if the node sent something other than RFC 3339.
+    pub fn block_time(&self) -> Option<NaiveDateTime> {
+        DateTime::parse_from_rfc3339(&self.block.header.time)
+            .ok()
+            .map(|t| t.naive_utc())
     }
 
-    pub fn decode(json: String) -> Result<Self, String> {
+    pub fn decode(json: String) -> Result<Self, BlockFetchError> {
         match serde_json::from_str(&json) {
             Ok(block) => Ok(block),
-            Err(arg) => {
-                let block_error: BlockError = match serde_json::from_str(&json) {
-                    Ok(block_error_result) => block_error_result,
-                    Err(arg) => return Err(arg.to_string()),
-                };
-                if block_error.code == 3 {
-                    return Err("3".to_string());
-                }
-
-                Err(arg.to_string())
-            }
-        }
-    }
-
-
-        pub fn get_local_block_height() -> u64 {
-        let block_height: u64 = match fs::read_to_string(BLOCK_HEIGHT_FILE.as_str()) {
-            Ok(block_height_str) => match block_height_str.trim().parse::<u64>() {
-                Ok(block_height) => block_height,
-                Err(_) => {
-                    eprintln!("Failed to parse block height");
-                    1
-                }
+            Err(arg) => match serde_json::from_str::<BlockError>(&json) {
+                Ok(block_error) => Err(block_error.into()),
+                Err(_) => Err(BlockFetchError::Decode(arg.to_string())),
             },
-            Err(e) => {
-                eprintln!("Failed to read block height: {}", e);
-                1
-            }
-        };
-        block_height
-    }
-    pub fn write_local_block_height(block_height: u64) {
-        match fs::write(BLOCK_HEIGHT_FILE.as_str(), block_height.to_string()) {
-            Ok(_) => {}
-            Err(e) => eprintln!("Failed to write block height: {}", e),
         }
     }
-
 }
 
 use serde::{
@@ -297,10 +275,90 @@ use serde::{
     Deserializer,
 };
 use std::fmt;
-use std::fs;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::pubsub_chain::{build_http_client, request_url_with, RequestError};
+
+/// Where the indexer gets its blocks. `ChainClient` is the real one; tests
+/// substitute canned blocks.
+pub trait BlockSource: Send + Sync {
+    /// Height of the newest block on the chain.
+    fn latest_height(&self) -> Result<u64, BlockFetchError>;
+    /// The block at `height`.
+    fn block_at(&self, height: u64) -> Result<BlockRaw, BlockFetchError>;
+}
+
+/// Fetches blocks from a node's REST API.
+///
+/// Transient failures (connection errors, timeouts, 5xx) are retried with
+/// exponential backoff, up to `max_attempts` tries in total; 4xx responses
+/// fail fast.
+pub struct ChainClient {
+    base_url: String,
+    path_template: String,
+    http: reqwest::blocking::Client,
+    max_attempts: u32,
+}
+
+impl ChainClient {
+    /// A client for the node at `base_url`, fetching blocks from `path_template`
+    /// (with `{height}` substituted).
+    pub fn new(base_url: &str, path_template: &str, http: reqwest::blocking::Client, max_attempts: u32) -> ChainClient {
+        ChainClient {
+            base_url: base_url.to_string(),
+            path_template: path_template.to_string(),
+            http,
+            max_attempts: max_attempts.max(1),
+        }
+    }
+
+    /// A client for `NYKS_BLOCK_SUBSCRIBER_URL` with the configured timeouts and
+    /// retries, keeping one pooled connection per parallel block fetch.
+    pub fn from_config(config: &Config) -> ChainClient {
+        let http = build_http_client(
+            Duration::from_secs(config.request_connect_timeout_secs),
+            Duration::from_secs(config.request_timeout_secs),
+            config.sync_concurrency as usize,
+        );
+        ChainClient::new(&config.block_subscriber_url, &config.block_path_template, http, config.request_max_attempts)
+    }
+
+    /// The node's JSON for the block at `height` (or `latest`), undecoded; this
+    /// is what the block fixtures under `tests/fixtures/blocks` hold.
+    pub fn block_json(&self, height: &str) -> Result<String, RequestError> {
+        request_url_with(&self.http, &block_url(&self.base_url, &self.path_template, height), self.max_attempts)
+    }
+
+    /// The block at `height`, which may also be `latest`.
+    fn fetch(&self, height: &str) -> Result<BlockRaw, BlockFetchError> {
+        block_from_response(self.block_json(height))
+    }
+}
 
-use crate::pubsub_chain::request_url;
-use crate::pubsub_chain::NYKS_BLOCK_SUBSCRIBER_URL;
+impl BlockSource for ChainClient {
+    fn latest_height(&self) -> Result<u64, BlockFetchError> {
+        self.fetch("latest").map(|block| block.block.header.height)
+    }
+
+    fn block_at(&self, height: u64) -> Result<BlockRaw, BlockFetchError> {
+        self.fetch(&height.to_string())
+    }
+}
+
+/// The block in a REST response, or why there isn't one.
+fn block_from_response(response: Result<String, RequestError>) -> Result<BlockRaw, BlockFetchError> {
+    match response {
+        Ok(body) => BlockRaw::decode(body),
+        // 4xx bodies carry the gRPC error code (e.g. 3 for a height the node hasn't reached)
+        Err(RequestError::Client { status, body }) => match serde_json::from_str::<BlockError>(&body) {
+            Ok(block_error) => Err(block_error.into()),
+            Err(_) if status == 404 => Err(BlockFetchError::NotFound),
+            Err(_) => Err(BlockFetchError::Decode(format!("HTTP {}: {}", status, body))),
+        },
+        Err(RequestError::Transient(arg)) => Err(BlockFetchError::Transient(arg)),
+    }
+}
 /// Custom deserializer for converting a string to a `u64`.
 ///
 /// Used for fields that are serialized as strings in the JSON API.
@@ -338,62 +396,21 @@ where
 mod tests {
     use super::*;
 
+    /// Block 1 as served by the nyks REST API.
+    const BLOCK_1: &str = include_str!("../tests/fixtures/blocks/1.json");
+
+    #[test]
+    fn block_url_substitutes_the_height() {
+        assert_eq!(
+            block_url("http://localhost:1317/", crate::config::DEFAULT_BLOCK_PATH_TEMPLATE, "42"),
+            "http://localhost:1317/cosmos/base/tendermint/v1beta1/blocks/42"
+        );
+        assert_eq!(block_url("https://lcd.example", "blocks/{height}", "latest"), "https://lcd.example/blocks/latest");
+    }
+
     #[test]
     fn test_block_raw_decode_height_1() {
-        let json_str = r#"{
-            "block_id": {
-                "hash": "ZxPZxXEc3r9uJVgqCjeqRAnrSWzv5QUg+AVWXO4BcK4=",
-                "part_set_header": {
-                    "total": 1,
-                    "hash": "sBIbv/9Gfqx9MzgmCYETc0Nx32fcclNxz+9R3MSuMF0="
-                }
-            },
-            "block": {
-                "header": {
-                    "version": {
-                        "block": "11",
-                        "app": "0"
-                    },
-                    "chain_id": "nyks",
-                    "height": "1",
-                    "time": "2025-06-13T12:55:07.001017274Z",
-                    "last_block_id": {
-                        "hash": null,
-                        "part_set_header": {
-                            "total": 0,
-                            "hash": null
-                        }
-                    },
-                    "last_commit_hash": "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=",
-                    "data_hash": "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=",
-                    "validators_hash": "2JXZzU66O/FG00NQMm6Z77VAOWFiqY494wVoQKe47uk=",
-                    "next_validators_hash": "2JXZzU66O/FG00NQMm6Z77VAOWFiqY494wVoQKe47uk=",
-                    "consensus_hash": "BICRvH3cKD93v7+R1zxE2ljD34qcvIZ0Bdi389qtoi8=",
-                    "app_hash": "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=",
-                    "last_results_hash": "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=",
-                    "evidence_hash": "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=",
-                    "proposer_address": "GTahVJYnvUCi88UM6EZDxgJEHVM="
-                },
-                "data": {
-                    "txs": []
-                },
-                "evidence": {
-                    "evidence": []
-                },
-                "last_commit": {
-                    "height": "0",
-                    "round": 0,
-                    "block_id": {
-                        "hash": null,
-                        "part_set_header": {
-                            "total": 0,
-                            "hash": null
-                        }
-                    },
-                    "signatures": []
-                }
-            }
-        }"#;
+        let json_str = BLOCK_1;
 
         let block_raw: BlockRaw = match serde_json::from_str(json_str) {
             Ok(block_raw) => block_raw,
@@ -410,72 +427,16 @@ mod tests {
         assert_eq!(block_raw.block_id.part_set_header.total, 1);
         assert_eq!(block_raw.block.header.chain_id, "nyks");
         assert_eq!(block_raw.block.header.height, 1);
+        assert_eq!(
+            block_raw.block_time().map(|t| t.to_string()),
+            Some("2025-06-13 12:55:07.001017274".to_string())
+        );
         assert!(block_raw.block.data.txs.is_empty());
     }
 
     #[test]
     fn test_block_raw_decode_height_2() {
-        let json_str = r#"{
-            "block_id": {
-                "hash": "yK6nk0XNaGa/yxmsPYSo9imvpPm1KKb0Pl1GHeEKgfc=",
-                "part_set_header": {
-                "total": 1,
-                "hash": "QgWBa+3nEyKl3WHjpyt7/W9/ABVfFjYhZZd6etUQRsc="
-                }
-            },
-            "block": {
-                "header": {
-                "version": {
-                    "block": "11",
-                    "app": "0"
-                },
-                "chain_id": "nyks",
-                "height": "2",
-                "time": "2025-06-13T13:06:36.719861789Z",
-                "last_block_id": {
-                    "hash": "ZxPZxXEc3r9uJVgqCjeqRAnrSWzv5QUg+AVWXO4BcK4=",
-                    "part_set_header": {
-                    "total": 1,
-                    "hash": "sBIbv/9Gfqx9MzgmCYETc0Nx32fcclNxz+9R3MSuMF0="
-                    }
-                },
-                "last_commit_hash": "frt1X5vTIn8dwMnQBsrlIzwNu+itG/yS+Hpbk1B5jMA=",
-                "data_hash": "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=",
-                "validators_hash": "2JXZzU66O/FG00NQMm6Z77VAOWFiqY494wVoQKe47uk=",
-                "next_validators_hash": "2JXZzU66O/FG00NQMm6Z77VAOWFiqY494wVoQKe47uk=",
-                "consensus_hash": "BICRvH3cKD93v7+R1zxE2ljD34qcvIZ0Bdi389qtoi8=",
-                "app_hash": "cmM8gjlrWjhTB/TfQLIbf1skbm0J8D7ieJr+4XEDKug=",
-                "last_results_hash": "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=",
-                "evidence_hash": "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=",
-                "proposer_address": "GTahVJYnvUCi88UM6EZDxgJEHVM="
-                },
-                "data": {
-                "txs": [ ]
-                },
-                "evidence": {
-                "evidence": [ ]
-                },
-                "last_commit": {
-                "height": "1",
-                "round": 0,
-                "block_id": {
-                    "hash": "ZxPZxXEc3r9uJVgqCjeqRAnrSWzv5QUg+AVWXO4BcK4=",
-                    "part_set_header": {
-                    "total": 1,
-                    "hash": "sBIbv/9Gfqx9MzgmCYETc0Nx32fcclNxz+9R3MSuMF0="
-                    }
-                },
-                "signatures": [
-                    {
-                    "block_id_flag": "BLOCK_ID_FLAG_COMMIT",
-                    "validator_address": "GTahVJYnvUCi88UM6EZDxgJEHVM=",
-                    "timestamp": "2025-06-13T13:06:36.719861789Z",
-                    "signature": "OXGW+sa3kt7fmUFItAaenP1pMVhTrz5tXKQvNFEhKa8VYHRPjA/YXaDcLFI5hgoW8kmQPSDjXpOvqv19ylY5CA=="
-                    }
-                ]
-                }
-            }
-            }"#;
+        let json_str = include_str!("../tests/fixtures/blocks/2.json");
 
         let block_raw: BlockRaw = match serde_json::from_str(json_str) {
             Ok(block_raw) => block_raw,
@@ -494,4 +455,78 @@ mod tests {
         assert_eq!(block_raw.block.header.height, 2);
         assert!(block_raw.block.data.txs.is_empty());
     }
+
+    #[test]
+    fn test_block_raw_decode_errors() {
+        let ahead = r#"{"code":3,"message":"requested block height is bigger then the chain length","details":[]}"#;
+        assert_eq!(
+            BlockRaw::decode(ahead.to_string()).unwrap_err(),
+            BlockFetchError::HeightNotYetAvailable
+        );
+
+        let missing = r#"{"code":5,"message":"block not found","details":[]}"#;
+        assert_eq!(BlockRaw::decode(missing.to_string()).unwrap_err(), BlockFetchError::NotFound);
+
+        let internal = r#"{"code":13,"message":"boom","details":[]}"#;
+        assert!(matches!(
+            BlockRaw::decode(internal.to_string()),
+            Err(BlockFetchError::Transient(_))
+        ));
+
+        assert!(matches!(
+            BlockRaw::decode("not json".to_string()),
+            Err(BlockFetchError::Decode(_))
+        ));
+    }
+
+    /// Answers a single request on a local port with `status` and `body`, and
+    /// returns the node URL to point a `ChainClient` at.
+    fn serve_once(status: &'static str, body: &'static str) -> String {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let _ = stream.read(&mut [0; 4096]);
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        url
+    }
+
+    fn client_for(url: &str) -> ChainClient {
+        let http = build_http_client(Duration::from_secs(1), Duration::from_secs(5), 1);
+        ChainClient::new(url, crate::config::DEFAULT_BLOCK_PATH_TEMPLATE, http, 1)
+    }
+
+    #[test]
+    fn chain_client_fetches_blocks() {
+        let block = client_for(&serve_once("200 OK", BLOCK_1)).block_at(1).unwrap();
+        assert_eq!(block.block_id.hash, "ZxPZxXEc3r9uJVgqCjeqRAnrSWzv5QUg+AVWXO4BcK4=");
+
+        assert_eq!(client_for(&serve_once("200 OK", BLOCK_1)).latest_height(), Ok(1));
+    }
+
+    #[test]
+    fn chain_client_maps_error_responses() {
+        let ahead = r#"{"code":3,"message":"requested block height is bigger then the chain length","details":[]}"#;
+        assert_eq!(
+            client_for(&serve_once("400 Bad Request", ahead)).block_at(9_999_999),
+            Err(BlockFetchError::HeightNotYetAvailable)
+        );
+        assert_eq!(
+            client_for(&serve_once("404 Not Found", "no such route")).block_at(5),
+            Err(BlockFetchError::NotFound)
+        );
+        assert!(matches!(
+            client_for(&serve_once("503 Service Unavailable", "")).block_at(5),
+            Err(BlockFetchError::Transient(_))
+        ));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,501 @@
+//! Every setting the binary reads from the environment, in one typed struct.
+//!
+//! `Config::from_env` is called once in `main`; the result is passed to the
+//! API, the indexer and the database module instead of each reading env vars
+//! on its own. Every value is checked up front and all problems are reported
+//! together, so a misconfigured deployment fails at startup with one readable
+//! message instead of panicking on the first bad value (or on the first write).
+use crate::quis_quis_tx::DecodeFormat;
+use reqwest::Url;
+use std::fmt;
+use std::str::FromStr;
+
+pub const DEFAULT_DB_POOL_SIZE: u32 = 10;
+pub const DEFAULT_INDEXER_ID: &str = "default";
+pub const DEFAULT_API_HOST: &str = "127.0.0.1";
+pub const DEFAULT_API_PORT: u16 = 8080;
+pub const DEFAULT_RATE_LIMIT_RPS: f64 = 10.0;
+pub const DEFAULT_RATE_LIMIT_BURST: u32 = 20;
+pub const DEFAULT_DECODE_MAX_INPUT_LEN: usize = 1024 * 1024;
+pub const DEFAULT_BLOCK_SUBSCRIBER_URL: &str = "http://localhost:1317/";
+pub const DEFAULT_BLOCK_PATH_TEMPLATE: &str = "/cosmos/base/tendermint/v1beta1/blocks/{height}";
+pub const DEFAULT_BLOCK_HEIGHT_FILE: &str = "height.txt";
+pub const DEFAULT_WS_URL: &str = "ws://localhost:26657/websocket";
+pub const DEFAULT_POLL_INTERVAL_SECS: u64 = 30;
+pub const DEFAULT_REQUEST_MAX_ATTEMPTS: u32 = 5;
+pub const DEFAULT_REQUEST_CONNECT_TIMEOUT_SECS: u64 = 5;
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+pub const DEFAULT_SYNC_CONCURRENCY: u64 = 4;
+pub const DEFAULT_HEIGHT_COMMIT_INTERVAL: u64 = 1;
+pub const DEFAULT_CHAIN_MAX_WAIT_SECS: u64 = 600;
+pub const DEFAULT_INDEXER_MAX_RESTARTS: u32 = 5;
+pub const DEFAULT_MIGRATION_MAX_WAIT_SECS: u64 = 120;
+
+/// How the indexer learns about new blocks (`BLOCK_SUBSCRIBE_MODE`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscribeMode {
+    /// Check for new blocks every `BLOCK_POLL_INTERVAL_SECS`.
+    Poll,
+    /// Follow CometBFT `NewBlock` events from `NYKS_WS_URL`.
+    Ws,
+}
+
+impl FromStr for SubscribeMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "poll" => Ok(SubscribeMode::Poll),
+            "ws" => Ok(SubscribeMode::Ws),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    // ----- database -----
+    /// `DATABASE_URL` (required).
+    pub database_url: String,
+    /// `DB_POOL_SIZE`: maximum pooled connections.
+    pub db_pool_size: u32,
+    /// `ENABLE_DELTA_LOG`: record every stat change in `height_deltas`.
+    pub enable_delta_log: bool,
+    /// `INDEXER_ID`: row key for this indexer's progress in `indexer_state`.
+    pub indexer_id: String,
+    /// `SUMMARY_REFRESH_SECS`: how often `global_totals` is recomputed for the
+    /// totals endpoints; 0 (default) sums the tables on each request instead.
+    pub summary_refresh_secs: u64,
+    /// `SKIP_MIGRATIONS`: don't run migrations at startup (they are applied externally).
+    pub skip_migrations: bool,
+    /// `MIGRATION_MAX_WAIT_SECS`: how long startup keeps retrying migrations while
+    /// the database is unreachable; 0 retries forever.
+    pub migration_max_wait_secs: u64,
+
+    // ----- API -----
+    pub enable_api: bool,
+    pub api_host: String,
+    pub api_port: u16,
+    /// `API_ALLOWED_ORIGINS`: `None` allows any origin.
+    pub api_allowed_origins: Option<Vec<String>>,
+    /// `ADMIN_TOKEN`: `None` disables the admin endpoints.
+    pub admin_token: Option<String>,
+    /// `RATE_LIMIT_RPS`: requests per second per client IP; 0 turns limiting off.
+    pub rate_limit_rps: f64,
+    pub rate_limit_burst: u32,
+    /// `DECODE_MAX_INPUT_LEN`: longest `tx_byte_code` `/api/decode-transaction` accepts, in chars.
+    pub decode_max_input_len: usize,
+
+    // ----- indexer -----
+    pub enable_indexer: bool,
+    /// `NYKS_BLOCK_SUBSCRIBER_URL`: the chain's REST (LCD) endpoint.
+    pub block_subscriber_url: String,
+    /// `BLOCK_PATH_TEMPLATE`: block path under `block_subscriber_url`, `{height}` substituted.
+    pub block_path_template: String,
+    /// `BLOCK_HEIGHT_FILE`: legacy progress file, read once when `indexer_state` is empty.
+    pub block_height_file: String,
+    /// `START_HEIGHT`: first height to index when the saved progress is below it.
+    pub start_height: Option<u64>,
+    pub subscribe_mode: SubscribeMode,
+    /// `NYKS_WS_URL`: CometBFT RPC websocket for `SubscribeMode::Ws`.
+    pub ws_url: String,
+    pub poll_interval_secs: u64,
+    pub request_max_attempts: u32,
+    pub request_connect_timeout_secs: u64,
+    pub request_timeout_secs: u64,
+    pub sync_concurrency: u64,
+    pub reorg_depth: u64,
+    /// `CONFIRMATION_LAG`: blocks the indexer stays behind the chain tip, so it
+    /// only indexes blocks that are unlikely to be reorganised away.
+    pub confirmation_lag: u64,
+    pub height_commit_interval: u64,
+    /// `CHAIN_MAX_WAIT_SECS`: how long the indexer keeps retrying an unreachable
+    /// node at startup before giving up; 0 retries forever.
+    pub chain_max_wait_secs: u64,
+    /// `INDEXER_MAX_RESTARTS`: times a panicked indexer is restarted before it is left stopped.
+    pub indexer_max_restarts: u32,
+    /// `INDEXER_DRY_RUN`: decode blocks without writing stats or the indexed height.
+    pub dry_run: bool,
+    /// `OUTPUT_NDJSON_PATH`: also write every decoded message as a JSON line to
+    /// this file, or to stdout for `-`; `None` (unset) writes nothing.
+    pub output_ndjson_path: Option<String>,
+    /// `SKIP_TYPE_URLS`: message types (without the leading `/`) that the indexer
+    /// counts but doesn't decode or record stats for.
+    pub skip_type_urls: Vec<String>,
+    pub qq_decode_format: DecodeFormat,
+}
+
+/// Every problem found in the environment.
+#[derive(Debug, PartialEq)]
+pub struct ConfigError(pub Vec<String>);
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid configuration ({} problem(s)):", self.0.len())?;
+        for problem in &self.0 {
+            write!(f, "\n  - {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    pub fn from_env() -> Result<Config, ConfigError> {
+        Config::from_vars(|name| std::env::var(name).ok())
+    }
+
+    /// Defaults everywhere, with `DATABASE_URL` taken from the environment when set
+    /// so database tests reach the test instance.
+    #[cfg(test)]
+    pub fn test_default() -> Config {
+        let database_url = std::env::var("DATABASE_URL")
+            .unwrap_or_else(|_| "postgres://postgres@127.0.0.1/stats_test".to_string());
+        Config::from_vars(|name| (name == "DATABASE_URL").then(|| database_url.clone()))
+            .expect("defaults are valid")
+    }
+
+    /// Builds the config from `var` (an environment lookup), collecting every problem.
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Config, ConfigError> {
+        let mut p = Vec::new();
+        let problems = &mut p;
+
+        // Never echo the value back: it usually carries the password
+        let database_url = match var("DATABASE_URL").filter(|url| !url.trim().is_empty()) {
+            Some(url) if url.starts_with("postgres://") || url.starts_with("postgresql://") => url,
+            Some(_) => {
+                problems.push("DATABASE_URL must be a postgres:// or postgresql:// URL".to_string());
+                String::new()
+            }
+            None => {
+                problems.push("DATABASE_URL must be set".to_string());
+                String::new()
+            }
+        };
+        let db_pool_size = positive(&var, "DB_POOL_SIZE", DEFAULT_DB_POOL_SIZE, problems);
+        let enable_delta_log = flag(&var, "ENABLE_DELTA_LOG", false, problems);
+        let indexer_id = non_empty(&var, "INDEXER_ID", DEFAULT_INDEXER_ID, problems);
+        let summary_refresh_secs = parse_var(
+            &var,
+            "SUMMARY_REFRESH_SECS",
+            0,
+            "a number of seconds (0 to disable)",
+            |_| true,
+            problems,
+        );
+
+        let skip_migrations = flag(&var, "SKIP_MIGRATIONS", false, problems);
+        let migration_max_wait_secs = parse_var(
+            &var,
+            "MIGRATION_MAX_WAIT_SECS",
+            DEFAULT_MIGRATION_MAX_WAIT_SECS,
+            "a number of seconds (0 to wait forever)",
+            |_| true,
+            problems,
+        );
+
+        let enable_api = flag(&var, "ENABLE_API", true, problems);
+        let api_host = non_empty(&var, "API_HOST", DEFAULT_API_HOST, problems);
+        let api_port = parse_var(&var, "API_PORT", DEFAULT_API_PORT, "a port number (0-65535)", |_| true, problems);
+        let api_allowed_origins = parse_allowed_origins(var("API_ALLOWED_ORIGINS").as_deref());
+        let admin_token = var("ADMIN_TOKEN").filter(|token| !token.is_empty());
+        let rate_limit_rps = parse_var(
+            &var,
+            "RATE_LIMIT_RPS",
+            DEFAULT_RATE_LIMIT_RPS,
+            "a number of requests per second (0 to disable)",
+            |rps: &f64| rps.is_finite() && *rps >= 0.0,
+            problems,
+        );
+        let rate_limit_burst = positive(&var, "RATE_LIMIT_BURST", DEFAULT_RATE_LIMIT_BURST, problems);
+        let decode_max_input_len = parse_var(
+            &var,
+            "DECODE_MAX_INPUT_LEN",
+            DEFAULT_DECODE_MAX_INPUT_LEN,
+            "a whole number of at least 1",
+            |len: &usize| *len >= 1,
+            problems,
+        );
+
+        let enable_indexer = flag(&var, "ENABLE_INDEXER", true, problems);
+        let block_subscriber_url = url_var(
+            &var,
+            "NYKS_BLOCK_SUBSCRIBER_URL",
+            DEFAULT_BLOCK_SUBSCRIBER_URL,
+            &["http", "https"],
+            problems,
+        );
+        let block_path_template = non_empty(&var, "BLOCK_PATH_TEMPLATE", DEFAULT_BLOCK_PATH_TEMPLATE, problems);
+        let block_height_file = non_empty(&var, "BLOCK_HEIGHT_FILE", DEFAULT_BLOCK_HEIGHT_FILE, problems);
+        let start_height = var("START_HEIGHT").map(|_| positive(&var, "START_HEIGHT", 1, problems));
+        let subscribe_mode =
+            parse_var(&var, "BLOCK_SUBSCRIBE_MODE", SubscribeMode::Poll, "poll or ws", |_| true, problems);
+        let ws_url = url_var(&var, "NYKS_WS_URL", DEFAULT_WS_URL, &["ws", "wss"], problems);
+        let poll_interval_secs = positive(&var, "BLOCK_POLL_INTERVAL_SECS", DEFAULT_POLL_INTERVAL_SECS, problems);
+        let request_max_attempts = positive(&var, "REQUEST_MAX_ATTEMPTS", DEFAULT_REQUEST_MAX_ATTEMPTS, problems);
+        let request_connect_timeout_secs =
+            positive(&var, "REQUEST_CONNECT_TIMEOUT_SECS", DEFAULT_REQUEST_CONNECT_TIMEOUT_SECS, problems);
+        let request_timeout_secs = positive(&var, "REQUEST_TIMEOUT_SECS", DEFAULT_REQUEST_TIMEOUT_SECS, problems);
+        let sync_concurrency = positive(&var, "SYNC_CONCURRENCY", DEFAULT_SYNC_CONCURRENCY, problems);
+        let reorg_depth = parse_var(&var, "REORG_DEPTH", 0, "a number of blocks", |_| true, problems);
+        let confirmation_lag = parse_var(&var, "CONFIRMATION_LAG", 0, "a number of blocks", |_| true, problems);
+        let height_commit_interval =
+            positive(&var, "HEIGHT_COMMIT_INTERVAL", DEFAULT_HEIGHT_COMMIT_INTERVAL, problems);
+        let chain_max_wait_secs = parse_var(
+            &var,
+            "CHAIN_MAX_WAIT_SECS",
+            DEFAULT_CHAIN_MAX_WAIT_SECS,
+            "a number of seconds (0 to wait forever)",
+            |_| true,
+            problems,
+        );
+        let indexer_max_restarts = parse_var(
+            &var,
+            "INDEXER_MAX_RESTARTS",
+            DEFAULT_INDEXER_MAX_RESTARTS,
+            "a number of restarts",
+            |_| true,
+            problems,
+        );
+        let dry_run = flag(&var, "INDEXER_DRY_RUN", false, problems);
+        let output_ndjson_path = var("OUTPUT_NDJSON_PATH").map(|_| non_empty(&var, "OUTPUT_NDJSON_PATH", "-", problems));
+        let skip_type_urls = parse_type_urls(var("SKIP_TYPE_URLS").as_deref());
+        let qq_decode_format = parse_var(
+            &var,
+            "QQ_DECODE_FORMAT",
+            DecodeFormat::Auto,
+            "bincode, postcard or auto",
+            |_| true,
+            problems,
+        );
+
+        if !p.is_empty() {
+            return Err(ConfigError(p));
+        }
+        Ok(Config {
+            database_url,
+            db_pool_size,
+            enable_delta_log,
+            indexer_id,
+            summary_refresh_secs,
+            skip_migrations,
+            migration_max_wait_secs,
+            enable_api,
+            api_host,
+            api_port,
+            api_allowed_origins,
+            admin_token,
+            rate_limit_rps,
+            rate_limit_burst,
+            decode_max_input_len,
+            enable_indexer,
+            block_subscriber_url,
+            block_path_template,
+            block_height_file,
+            start_height,
+            subscribe_mode,
+            ws_url,
+            poll_interval_secs,
+            request_max_attempts,
+            request_connect_timeout_secs,
+            request_timeout_secs,
+            sync_concurrency,
+            reorg_depth,
+            confirmation_lag,
+            height_commit_interval,
+            chain_max_wait_secs,
+            indexer_max_restarts,
+            dry_run,
+            output_ndjson_path,
+            skip_type_urls,
+            qq_decode_format,
+        })
+    }
+}
+
+/// `name` parsed as `T`, or `default` when unset. A value that doesn't parse or
+/// fails `valid` is recorded in `problems` (and `default` returned so checking
+/// can continue).
+fn parse_var<T: FromStr>(
+    var: &impl Fn(&str) -> Option<String>,
+    name: &str,
+    default: T,
+    expected: &str,
+    valid: impl Fn(&T) -> bool,
+    problems: &mut Vec<String>,
+) -> T {
+    let Some(value) = var(name) else {
+        return default;
+    };
+    match value.trim().parse::<T>() {
+        Ok(parsed) if valid(&parsed) => parsed,
+        _ => {
+            problems.push(format!("{}={:?} must be {}", name, value, expected));
+            default
+        }
+    }
+}
+
+fn flag(var: &impl Fn(&str) -> Option<String>, name: &str, default: bool, problems: &mut Vec<String>) -> bool {
+    parse_var(var, name, default, "true or false", |_| true, problems)
+}
+
+/// A count that must be at least 1.
+fn positive<T: FromStr + PartialOrd + From<u8>>(
+    var: &impl Fn(&str) -> Option<String>,
+    name: &str,
+    default: T,
+    problems: &mut Vec<String>,
+) -> T {
+    parse_var(var, name, default, "a whole number of at least 1", |n| *n >= T::from(1), problems)
+}
+
+fn non_empty(var: &impl Fn(&str) -> Option<String>, name: &str, default: &str, problems: &mut Vec<String>) -> String {
+    match var(name) {
+        None => default.to_string(),
+        Some(value) if value.trim().is_empty() => {
+            problems.push(format!("{} must not be empty", name));
+            default.to_string()
+        }
+        Some(value) => value,
+    }
+}
+
+/// `name` as a URL with one of `schemes`, or `default` when unset.
+fn url_var(
+    var: &impl Fn(&str) -> Option<String>,
+    name: &str,
+    default: &str,
+    schemes: &[&str],
+    problems: &mut Vec<String>,
+) -> String {
+    let Some(value) = var(name) else {
+        return default.to_string();
+    };
+    match Url::parse(value.trim()) {
+        Ok(url) if schemes.contains(&url.scheme()) => value.trim().to_string(),
+        _ => {
+            problems.push(format!("{}={:?} must be a {} URL", name, value, schemes.join("/")));
+            default.to_string()
+        }
+    }
+}
+
+/// Splits `API_ALLOWED_ORIGINS` on commas; `None` when unset or empty.
+fn parse_allowed_origins(value: Option<&str>) -> Option<Vec<String>> {
+    let origins: Vec<String> = value?
+        .split(',')
+        .map(|origin| origin.trim().trim_end_matches('/'))
+        .filter(|origin| !origin.is_empty())
+        .map(str::to_string)
+        .collect();
+    (!origins.is_empty()).then_some(origins)
+}
+
+/// Splits `SKIP_TYPE_URLS` on commas, dropping the leading `/` of each type URL.
+fn parse_type_urls(value: Option<&str>) -> Vec<String> {
+    value
+        .unwrap_or("")
+        .split(',')
+        .map(|type_url| type_url.trim().trim_start_matches('/'))
+        .filter(|type_url| !type_url.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn config_from(vars: &[(&str, &str)]) -> Result<Config, ConfigError> {
+        let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        Config::from_vars(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn only_database_url_is_required() {
+        let config = config_from(&[("DATABASE_URL", "postgres://u:p@localhost/stats")]).unwrap();
+        assert_eq!(config.api_host, DEFAULT_API_HOST);
+        assert_eq!(config.api_port, DEFAULT_API_PORT);
+        assert!(config.enable_api && config.enable_indexer);
+        assert_eq!(config.subscribe_mode, SubscribeMode::Poll);
+        assert_eq!(config.block_subscriber_url, DEFAULT_BLOCK_SUBSCRIBER_URL);
+        assert_eq!((config.admin_token, config.api_allowed_origins), (None, None));
+        assert!(!config.dry_run);
+        assert_eq!(config.chain_max_wait_secs, DEFAULT_CHAIN_MAX_WAIT_SECS);
+        assert_eq!(config.start_height, None);
+        assert_eq!(config.summary_refresh_secs, 0);
+        assert_eq!(config.output_ndjson_path, None);
+        assert!(!config.skip_migrations);
+        assert!(config.skip_type_urls.is_empty());
+        assert_eq!(config.decode_max_input_len, DEFAULT_DECODE_MAX_INPUT_LEN);
+        assert_eq!(config.migration_max_wait_secs, DEFAULT_MIGRATION_MAX_WAIT_SECS);
+        assert_eq!(config.confirmation_lag, 0);
+
+        let config = config_from(&[
+            ("DATABASE_URL", "postgresql://localhost/stats"),
+            ("API_PORT", "8449"),
+            ("ENABLE_API", "false"),
+            ("BLOCK_SUBSCRIBE_MODE", "ws"),
+            ("NYKS_WS_URL", "wss://rpc.example/websocket"),
+            ("SYNC_CONCURRENCY", "8"),
+            ("QQ_DECODE_FORMAT", "Postcard"),
+            ("CHAIN_MAX_WAIT_SECS", "0"),
+            ("START_HEIGHT", "400000"),
+            ("OUTPUT_NDJSON_PATH", "-"),
+            ("SKIP_MIGRATIONS", "true"),
+            ("SKIP_TYPE_URLS", "/pricefeed.v1.MsgPostPrice, cosmos.bank.v1beta1.MsgSend,"),
+            ("CONFIRMATION_LAG", "6"),
+        ])
+        .unwrap();
+        assert_eq!((config.api_port, config.enable_api), (8449, false));
+        assert_eq!(config.subscribe_mode, SubscribeMode::Ws);
+        assert_eq!(config.ws_url, "wss://rpc.example/websocket");
+        assert_eq!(config.sync_concurrency, 8);
+        assert_eq!(config.qq_decode_format, DecodeFormat::Postcard);
+        assert_eq!(config.chain_max_wait_secs, 0);
+        assert_eq!(config.start_height, Some(400_000));
+        assert_eq!(config.output_ndjson_path.as_deref(), Some("-"));
+        assert!(config.skip_migrations);
+        assert_eq!(config.skip_type_urls, ["pricefeed.v1.MsgPostPrice", "cosmos.bank.v1beta1.MsgSend"]);
+        assert_eq!(config.confirmation_lag, 6);
+    }
+
+    #[test]
+    fn every_problem_is_reported() {
+        let err = config_from(&[
+            ("API_PORT", "80800"),
+            ("ENABLE_INDEXER", "yes"),
+            ("INDEXER_DRY_RUN", "1"),
+            ("NYKS_BLOCK_SUBSCRIBER_URL", "lcd.twilight.rest"),
+            ("BLOCK_SUBSCRIBE_MODE", "push"),
+        ])
+        .unwrap_err();
+        assert_eq!(err.0.len(), 6, "{}", err);
+        assert_eq!(err.0[0], "DATABASE_URL must be set");
+
+        let err = config_from(&[("DATABASE_URL", "mysql://root:secret@db/stats")]).unwrap_err();
+        assert!(!err.to_string().contains("secret"));
+    }
+
+    #[test]
+    fn poll_interval_must_be_at_least_one_second() {
+        let with = |value: &str| config_from(&[("DATABASE_URL", "postgres://db/stats"), ("BLOCK_POLL_INTERVAL_SECS", value)]);
+        assert_eq!(Config::test_default().poll_interval_secs, 30);
+        assert_eq!(with("5").unwrap().poll_interval_secs, 5);
+        assert!(with("0").is_err());
+        assert!(with("soon").is_err());
+    }
+
+    #[test]
+    fn allowed_origins_are_split_and_trimmed() {
+        assert_eq!(parse_allowed_origins(None), None);
+        assert_eq!(parse_allowed_origins(Some(" , ")), None);
+        assert_eq!(
+            parse_allowed_origins(Some("https://app.example, https://admin.example/")),
+            Some(vec!["https://app.example".to_string(), "https://admin.example".to_string()])
+        );
+    }
+}
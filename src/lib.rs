@@ -1,3 +1,21 @@
+//! Twilight chain indexer: block fetching and decoding, the stats database and the REST API.
+//!
+//! The `twilight_indexer` binary is a thin wrapper over this crate; other tools can reuse the
+//! decoders (`transaction_types`, `quis_quis_tx`) and the block client
+//! (`block_types::ChainClient`) directly.
+pub mod api;
+pub mod block_types;
+pub mod config;
+pub mod db;
+pub mod ndjson;
+pub mod pubsub_chain;
+pub mod quis_quis_tx;
+pub mod transaction_types;
+mod live;
+mod metrics;
+mod rate_limit;
+mod schema;
+
 // Module path mirrors your `package twilightproject.nyks.bridge`:
 pub mod twilightproject {
     pub mod nyks {
@@ -9,3 +27,14 @@ pub mod twilightproject {
         }
     }
 }
+
+// Only MsgTransfer is vendored (proto/ibcTransfer.proto); cosmos-sdk-proto has no IBC types.
+pub mod ibc {
+    pub mod applications {
+        pub mod transfer {
+            pub mod v1 {
+                include!(concat!(env!("OUT_DIR"), "/ibc.applications.transfer.v1.rs"));
+            }
+        }
+    }
+}
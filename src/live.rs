@@ -0,0 +1,97 @@
+//! Fan-out of newly indexed transactions to `/api/ws/transactions` clients.
+//!
+//! The indexer publishes each block's decoded txs once the block is committed;
+//! every websocket connection holds a receiver. The channel is bounded and
+//! `send` never waits, so a client that falls more than `LIVE_CHANNEL_CAPACITY`
+//! txs behind misses the oldest ones (and is told how many) instead of slowing
+//! the indexer down.
+use crate::transaction_types::{type_name, DecodedTx};
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::{Arc, OnceLock};
+use tokio::sync::broadcast;
+
+/// Txs buffered per subscriber before the oldest are dropped for it.
+pub(crate) const LIVE_CHANNEL_CAPACITY: usize = 1024;
+
+static CHANNEL: OnceLock<broadcast::Sender<Arc<LiveTx>>> = OnceLock::new();
+
+/// A decoded tx as pushed to websocket clients.
+#[derive(Debug, Serialize)]
+pub(crate) struct LiveTx {
+    pub height: u64,
+    pub tx_index: usize,
+    /// `type_name` of each message, in order
+    pub types: Vec<&'static str>,
+    /// `StandardCosmosMsg::to_json` of each message
+    pub messages: Vec<Value>,
+}
+
+impl LiveTx {
+    pub(crate) fn new(height: u64, tx_index: usize, decoded: &DecodedTx) -> LiveTx {
+        LiveTx {
+            height,
+            tx_index,
+            types: decoded._messages.iter().map(type_name).collect(),
+            messages: decoded._messages.iter().map(|msg| msg.to_json()).collect(),
+        }
+    }
+
+    /// Whether any message is one of `types` (every tx when `types` is empty).
+    pub(crate) fn has_type(&self, types: &[String]) -> bool {
+        types.is_empty() || self.types.iter().any(|t| types.iter().any(|want| want == t))
+    }
+}
+
+fn sender() -> &'static broadcast::Sender<Arc<LiveTx>> {
+    CHANNEL.get_or_init(|| broadcast::channel(LIVE_CHANNEL_CAPACITY).0)
+}
+
+/// Whether anyone is listening, so the indexer can skip building `LiveTx`s.
+pub(crate) fn has_subscribers() -> bool {
+    sender().receiver_count() > 0
+}
+
+/// Hands `txs` to every current subscriber. Never blocks.
+pub(crate) fn publish(txs: Vec<LiveTx>) {
+    for tx in txs {
+        // Only fails when nobody is subscribed
+        let _ = sender().send(Arc::new(tx));
+    }
+}
+
+pub(crate) fn subscribe() -> broadcast::Receiver<Arc<LiveTx>> {
+    sender().subscribe()
+}
+
+/// Splits a `?type=` filter on commas, dropping a leading `/` from each type URL.
+pub(crate) fn parse_type_filter(filter: Option<&str>) -> Vec<String> {
+    filter
+        .unwrap_or("")
+        .split(',')
+        .map(|t| t.trim().trim_start_matches('/'))
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn type_filter_matches_any_message() {
+        let tx = LiveTx {
+            height: 1,
+            tx_index: 0,
+            types: vec!["cosmos.bank.v1beta1.MsgSend", "twilightproject.nyks.zkos.MsgTransferTx"],
+            messages: Vec::new(),
+        };
+        let filter = parse_type_filter(Some("/twilightproject.nyks.zkos.MsgTransferTx, cosmos.gov.v1beta1.MsgVote"));
+
+        assert_eq!(filter, ["twilightproject.nyks.zkos.MsgTransferTx", "cosmos.gov.v1beta1.MsgVote"]);
+        assert!(tx.has_type(&filter));
+        assert!(tx.has_type(&parse_type_filter(None)));
+        assert!(!tx.has_type(&parse_type_filter(Some("cosmos.gov.v1beta1.MsgVote"))));
+    }
+}
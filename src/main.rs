@@ -1,60 +1,187 @@
-mod block_types;
-mod db;
-mod pubsub_chain;
-mod transaction_types;
-mod schema;
-mod quis_quis_tx;
-mod api;
+use twilight_indexer::{api, config, db, ndjson, pubsub_chain, quis_quis_tx};
 
-use quis_quis_tx::decode_qq_transaction;
+use log::{error, info, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Flips `shutdown` on SIGINT (and SIGTERM on unix) so the indexer can stop
+/// between blocks instead of being killed mid-write.
+fn install_shutdown_handler(shutdown: Arc<AtomicBool>) {
+    let flag = shutdown.clone();
+    actix_web::rt::spawn(async move {
+        if actix_web::rt::signal::ctrl_c().await.is_ok() {
+            info!("Received ctrl-c, stopping indexer after the current block...");
+            flag.store(true, Ordering::SeqCst);
+        }
+    });
+
+    #[cfg(unix)]
+    actix_web::rt::spawn(async move {
+        use actix_web::rt::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                if terminate.recv().await.is_some() {
+                    info!("Received SIGTERM, stopping indexer after the current block...");
+                    shutdown.store(true, Ordering::SeqCst);
+                }
+            }
+            Err(e) => warn!("Failed to install SIGTERM handler: {}", e),
+        }
+    });
+}
+
+/// What the binary was asked to do on the command line.
+#[derive(Debug, PartialEq)]
+enum Command {
+    /// No arguments: run the API and/or indexer as configured by the environment.
+    Run,
+    /// `backfill --from A --to B`: index the heights in `A..=B` not indexed yet, and exit.
+    Backfill { from: u64, to: u64 },
+}
+
+const USAGE: &str = "usage: twilight_indexer [backfill --from <height> --to <height>]";
+
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    match args.first().map(String::as_str) {
+        None => Ok(Command::Run),
+        Some("backfill") => {
+            let (mut from, mut to) = (None, None);
+            let mut rest = args[1..].iter();
+            while let Some(flag) = rest.next() {
+                let slot = match flag.as_str() {
+                    "--from" => &mut from,
+                    "--to" => &mut to,
+                    other => return Err(format!("unknown backfill argument {:?}", other)),
+                };
+                let value = rest.next().ok_or_else(|| format!("{} needs a height", flag))?;
+                *slot = Some(value.parse::<u64>().map_err(|e| format!("invalid height {:?}: {}", value, e))?);
+            }
+            match (from, to) {
+                (Some(from), Some(to)) if from <= to => Ok(Command::Backfill { from, to }),
+                (Some(from), Some(to)) => Err(format!("--from {} is above --to {}", from, to)),
+                _ => Err("backfill needs both --from and --to".to_string()),
+            }
+        }
+        Some(other) => Err(format!("unknown command {:?}", other)),
+    }
+}
 
 #[actix_web::main]
 async fn main() {
-    dotenv::dotenv().expect("Failed loading dotenv");
-    db::run_migrations().expect("Failed to run database migrations");
-
-    // Get configuration from environment variables
-    let api_host = std::env::var("API_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
-    let api_port = std::env::var("API_PORT")
-        .unwrap_or_else(|_| "8080".to_string())
-        .parse::<u16>()
-        .expect("API_PORT must be a valid port number");
-
-    let enable_api = std::env::var("ENABLE_API")
-        .unwrap_or_else(|_| "true".to_string())
-        .parse::<bool>()
-        .unwrap_or(true);
-
-    let enable_indexer = std::env::var("ENABLE_INDEXER")
-        .unwrap_or_else(|_| "true".to_string())
-        .parse::<bool>()
-        .unwrap_or(true);
+    // .env is optional; settings can come from the real environment
+    dotenv::dotenv().ok();
+    // RUST_LOG controls verbosity (e.g. RUST_LOG=debug), defaulting to info
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = match parse_args(&args) {
+        Ok(command) => command,
+        Err(e) => {
+            error!("{}\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let config = match config::Config::from_env() {
+        Ok(config) => config,
+        Err(e) => {
+            error!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    db::init(&config);
+    pubsub_chain::init(&config);
+    quis_quis_tx::init_decode_format(config.qq_decode_format);
+    if let Some(path) = &config.output_ndjson_path {
+        if let Err(e) = ndjson::init(path) {
+            error!("Failed to open OUTPUT_NDJSON_PATH {:?}: {}", path, e);
+            std::process::exit(1);
+        }
+    }
+    if config.skip_migrations {
+        info!("SKIP_MIGRATIONS is set, leaving the schema to be migrated externally");
+    } else if let Err(e) = db::run_migrations_when_reachable(config.migration_max_wait_secs) {
+        // Without the indexer only API reads need the database, and each of those
+        // fails on its own until it is back; anything else can't run without the schema
+        let api_only = config.enable_api && !config.enable_indexer && command == Command::Run;
+        if api_only && db::is_connection_error(&e) {
+            error!("Failed to run database migrations, starting the API anyway: {:?}", e);
+        } else {
+            error!("Failed to run database migrations: {:?}", e);
+            std::process::exit(1);
+        }
+    }
+
+    let shutdown = Arc::new(AtomicBool::new(false));
+    install_shutdown_handler(shutdown.clone());
+
+    if let Command::Backfill { from, to } = command {
+        let _ = actix_web::rt::task::spawn_blocking(move || {
+            pubsub_chain::backfill_range(from, to, &shutdown);
+        })
+        .await;
+        return;
+    }
+
+    if config.enable_api && config.summary_refresh_secs > 0 {
+        actix_web::rt::spawn(api::refresh_summary_periodically(config.summary_refresh_secs));
+    }
 
     // Run both API server and indexer concurrently
-    if enable_api && enable_indexer {
-        println!("🚀 Starting both API server and blockchain indexer...");
+    if config.enable_api && config.enable_indexer {
+        info!("Starting both API server and blockchain indexer...");
         
-        // Spawn indexer in background thread (since it's blocking)
-        let indexer_handle = std::thread::spawn(|| {
-            pubsub_chain::subscribe_block();
+        // Run indexer on the blocking pool (it's blocking) so the runtime stays free for signals
+        let indexer_shutdown = shutdown.clone();
+        let indexer_config = config.clone();
+        let indexer_handle = actix_web::rt::task::spawn_blocking(move || {
+            pubsub_chain::run_supervised_indexer(&indexer_config, indexer_shutdown);
         });
 
         // Run API server in the current async runtime
-        if let Err(e) = api::start_api_server(&api_host, api_port).await {
-            eprintln!("❌ API server error: {}", e);
+        if let Err(e) = api::start_api_server(&config).await {
+            error!("API server error: {}", e);
         }
 
-        // Wait for indexer thread to complete (it runs indefinitely)
-        let _ = indexer_handle.join();
-    } else if enable_api {
-        println!("🚀 Starting API server only...");
-        if let Err(e) = api::start_api_server(&api_host, api_port).await {
-            eprintln!("❌ API server error: {}", e);
+        // The server only returns on shutdown (or error); stop the indexer too and wait for it
+        shutdown.store(true, Ordering::SeqCst);
+        let _ = indexer_handle.await;
+    } else if config.enable_api {
+        info!("Starting API server only...");
+        if let Err(e) = api::start_api_server(&config).await {
+            error!("API server error: {}", e);
         }
-    } else if enable_indexer {
-        println!("🚀 Starting blockchain indexer only...");
-        pubsub_chain::subscribe_block();
+    } else if config.enable_indexer {
+        info!("Starting blockchain indexer only...");
+        let indexer_shutdown = shutdown.clone();
+        let _ = actix_web::rt::task::spawn_blocking(move || {
+            pubsub_chain::run_supervised_indexer(&config, indexer_shutdown);
+        })
+        .await;
     } else {
-        println!("⚠️ Both API and indexer are disabled. Nothing to do.");
+        warn!("Both API and indexer are disabled. Nothing to do.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_args_test() {
+        assert_eq!(parse_args(&args(&[])), Ok(Command::Run));
+        assert_eq!(
+            parse_args(&args(&["backfill", "--to", "20", "--from", "10"])),
+            Ok(Command::Backfill { from: 10, to: 20 })
+        );
+        assert!(parse_args(&args(&["backfill", "--from", "10"])).is_err());
+        assert!(parse_args(&args(&["backfill", "--from", "20", "--to", "10"])).is_err());
+        assert!(parse_args(&args(&["backfill", "--from", "ten", "--to", "20"])).is_err());
+        assert!(parse_args(&args(&["reindex"])).is_err());
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,176 @@
+//! Newline-delimited JSON output of decoded transactions (`OUTPUT_NDJSON_PATH`).
+//!
+//! Every message of every decoded tx is written as one line,
+//! `{"height", "tx_index", "msg_index", "type", "data"}`, where `data` is
+//! `StandardCosmosMsg::to_json`. Output doesn't depend on the database writes,
+//! so it also runs with `INDEXER_DRY_RUN`. Lines are written when a block has
+//! been decoded, before its database transaction commits, so a block that is
+//! retried after a failed commit can appear twice.
+//!
+//! Output is buffered and flushed after each block. A file sink notices when
+//! its path was rotated away (renamed or deleted) and reopens the path.
+use crate::transaction_types::{type_name, StandardCosmosMsg};
+use log::warn;
+use serde_json::json;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::sync::{Mutex, OnceLock};
+
+/// `OUTPUT_NDJSON_PATH` value that selects stdout instead of a file.
+pub const STDOUT_PATH: &str = "-";
+
+/// The sink set up by `init`; no output while unset.
+static SINK: OnceLock<Mutex<Sink>> = OnceLock::new();
+
+enum Sink {
+    Stdout(BufWriter<io::Stdout>),
+    File { path: String, out: BufWriter<File> },
+}
+
+fn open_append(path: &str) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// Whether `file` is no longer what `path` names (moved by logrotate, or deleted).
+fn rotated(path: &str, file: &File) -> bool {
+    let Ok(at_path) = std::fs::metadata(path) else {
+        return true;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        file.metadata().map_or(true, |open| (open.dev(), open.ino()) != (at_path.dev(), at_path.ino()))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (file, at_path);
+        false
+    }
+}
+
+impl Sink {
+    fn open(path: &str) -> io::Result<Sink> {
+        if path == STDOUT_PATH {
+            return Ok(Sink::Stdout(BufWriter::new(io::stdout())));
+        }
+        Ok(Sink::File { path: path.to_string(), out: BufWriter::new(open_append(path)?) })
+    }
+
+    fn out(&mut self) -> &mut dyn Write {
+        match self {
+            Sink::Stdout(out) => out,
+            Sink::File { out, .. } => out,
+        }
+    }
+
+    /// Flushes, then reopens a rotated file so the next block goes to the new one.
+    fn flush(&mut self) -> io::Result<()> {
+        self.out().flush()?;
+        if let Sink::File { path, out } = self {
+            if rotated(path, out.get_ref()) {
+                *out = BufWriter::new(open_append(path)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Starts writing decoded txs to `path` (appending), or to stdout for `-`.
+/// Only the first call has an effect.
+pub fn init(path: &str) -> io::Result<()> {
+    if SINK.get().is_none() {
+        let sink = Sink::open(path)?;
+        let _ = SINK.set(Mutex::new(sink));
+    }
+    Ok(())
+}
+
+/// The NDJSON lines for one decoded tx, one per message.
+fn tx_lines(block_height: u64, tx_index: usize, messages: &[StandardCosmosMsg]) -> Vec<String> {
+    messages
+        .iter()
+        .enumerate()
+        .map(|(msg_index, msg)| {
+            json!({
+                "height": block_height,
+                "tx_index": tx_index,
+                "msg_index": msg_index,
+                "type": type_name(msg),
+                "data": msg.to_json(),
+            })
+            .to_string()
+        })
+        .collect()
+}
+
+/// Collects a block's lines, then writes them in one go with `write`.
+#[derive(Debug, Default)]
+pub(crate) struct BlockLines(Vec<String>);
+
+impl BlockLines {
+    /// Adds the tx's lines; nothing is collected while no sink is set up.
+    pub(crate) fn add_tx(&mut self, block_height: u64, tx_index: usize, messages: &[StandardCosmosMsg]) {
+        if SINK.get().is_some() {
+            self.0.extend(tx_lines(block_height, tx_index, messages));
+        }
+    }
+
+    /// Writes and flushes the block's lines. A write error is logged and the
+    /// block's lines dropped; indexing carries on.
+    pub(crate) fn write(self) {
+        let Some(sink) = SINK.get() else {
+            return;
+        };
+        let mut sink = sink.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let result = self
+            .0
+            .iter()
+            .try_for_each(|line| writeln!(sink.out(), "{}", line))
+            .and_then(|()| sink.flush());
+        if let Err(e) = result {
+            warn!("Failed to write decoded txs to OUTPUT_NDJSON_PATH: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
+    use cosmos_sdk_proto::cosmos::base::v1beta1::Coin;
+
+    #[test]
+    fn each_message_is_one_line() {
+        let send = MsgSend {
+            from_address: "twilight1from".to_string(),
+            to_address: "twilight1to".to_string(),
+            amount: vec![Coin { denom: "nyks".to_string(), amount: "5".to_string() }],
+        };
+        let messages = [StandardCosmosMsg::BankSend(send.clone()), StandardCosmosMsg::BankSend(send)];
+
+        let lines = tx_lines(120, 3, &messages);
+
+        assert_eq!(lines.len(), 2);
+        let line: serde_json::Value = serde_json::from_str(&lines[1]).unwrap();
+        let position = (line["height"].as_u64(), line["tx_index"].as_u64(), line["msg_index"].as_u64());
+        assert_eq!(position, (Some(120), Some(3), Some(1)));
+        assert_eq!(line["type"], type_name(&messages[1]));
+        assert_eq!(line["data"], messages[1].to_json());
+    }
+
+    #[test]
+    fn a_moved_file_counts_as_rotated() {
+        let dir = std::env::temp_dir().join(format!("ndjson-rotation-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("txs.ndjson");
+        let path = path.to_str().unwrap();
+        let file = open_append(path).unwrap();
+        assert!(!rotated(path, &file));
+
+        std::fs::rename(path, dir.join("txs.ndjson.1")).unwrap();
+        let moved = rotated(path, &file);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(moved);
+    }
+}
@@ -1,34 +1,162 @@
-use anyhow::{bail, Context, Result};
-use hex;
-use crate::db::insert_qq_tx;
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::{STANDARD as B64, URL_SAFE as B64_URL_SAFE};
+use base64::Engine as _;
+use crate::db::StatsWriter;
+use log::{debug, warn};
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::sync::OnceLock;
 
 use transaction::{Transaction, TransactionData, TransferTransaction, ScriptTransaction, Message};
+
+/// Why QQ transaction bytecode couldn't be decoded.
+#[derive(Debug)]
+pub enum QqDecodeError {
+    /// `0x`-prefixed input that isn't valid hex.
+    HexDecode(hex::FromHexError),
+    /// Unprefixed input that is neither hex nor (standard or URL-safe) base64.
+    Base64Decode(base64::DecodeError),
+    /// Bincode reported a format mismatch in `auto` mode and the postcard fallback failed too.
+    BincodeFormatMismatch { bincode: bincode::Error, postcard: postcard::Error },
+    /// The bytes are not a bincode-encoded `Transaction`.
+    Bincode(bincode::Error),
+    /// The bytes are not a postcard-encoded `Transaction`.
+    Postcard(postcard::Error),
+}
+
+impl QqDecodeError {
+    /// The `error_code` reported to API clients.
+    pub fn code(&self) -> DecodeErrorCode {
+        match self {
+            QqDecodeError::HexDecode(_) | QqDecodeError::Base64Decode(_) => DecodeErrorCode::InvalidHex,
+            QqDecodeError::Bincode(_) => DecodeErrorCode::BincodeFailed,
+            QqDecodeError::BincodeFormatMismatch { .. } | QqDecodeError::Postcard(_) => DecodeErrorCode::PostcardFailed,
+        }
+    }
+}
+
+impl fmt::Display for QqDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QqDecodeError::HexDecode(e) => write!(f, "Failed to decode hex string: {}", e),
+            QqDecodeError::Base64Decode(_) => write!(f, "Failed to decode tx_byte_code: not valid hex or base64"),
+            QqDecodeError::BincodeFormatMismatch { bincode, postcard } => write!(
+                f,
+                "bincode deserialization failed (possible format mismatch): {}; postcard fallback failed: {}",
+                bincode, postcard
+            ),
+            QqDecodeError::Bincode(e) => write!(f, "bincode deserialization failed: {}", e),
+            QqDecodeError::Postcard(e) => write!(f, "postcard deserialization failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for QqDecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            QqDecodeError::HexDecode(e) => Some(e),
+            QqDecodeError::Base64Decode(e) => Some(e),
+            QqDecodeError::BincodeFormatMismatch { postcard, .. } => Some(postcard),
+            QqDecodeError::Bincode(e) => Some(e),
+            QqDecodeError::Postcard(e) => Some(e),
+        }
+    }
+}
+
 /// Decode a string that may be base64 or hex into bytes.
-fn decode_str_to_bytes(s: &str) -> Result<Vec<u8>> {    
-    let clean = s.trim().strip_prefix("0x").unwrap_or(s);
-    let bytes = hex::decode(clean).context("Failed to decode hex string")?;
-    Ok(bytes)
+///
+/// Hex (with or without `0x`) is tried first, then standard and URL-safe base64.
+fn decode_str_to_bytes(s: &str) -> Result<Vec<u8>, QqDecodeError> {
+    let trimmed = s.trim();
+    if let Some(hex_str) = trimmed.strip_prefix("0x") {
+        return hex::decode(hex_str).map_err(QqDecodeError::HexDecode);
+    }
+    if let Ok(bytes) = hex::decode(trimmed) {
+        return Ok(bytes);
+    }
+    B64.decode(trimmed)
+        .or_else(|_| B64_URL_SAFE.decode(trimmed))
+        .map_err(QqDecodeError::Base64Decode)
 }
 
 /// Deserialize into the *full* Transaction (struct with tx_type + tx data).
-/// Tries bincode first; optionally falls back to postcard.
-pub fn decode_transaction(tx_byte_code: &str) -> Result<Transaction> {
+pub fn decode_transaction(tx_byte_code: &str) -> Result<Transaction, QqDecodeError> {
     let bytes = decode_str_to_bytes(tx_byte_code)?;
+    decode_transaction_bytes(&bytes)
+}
 
-    // 1) bincode → Transaction
-    match bincode::deserialize::<Transaction>(&bytes) {
-        Ok(t) => return Ok(t),
-        Err(e) => {
-            // If this looks like an enum discriminant error, add a nice hint
-            if e.to_string().contains("expected variant index") {
-                // This error pops up when bytes aren't from the expected format.
-                // We’ll try postcard next if enabled.
-                // For now, just return the error.
-                bail!("bincode deserialization failed (possible format mismatch): {e}");
-            } else {
-                // Other bincode errors—return the error.
-                bail!("bincode deserialization failed: {e}");
+/// Wire format of QQ transaction bytecode, chosen with `QQ_DECODE_FORMAT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeFormat {
+    Bincode,
+    Postcard,
+    /// Bincode, falling back to postcard when bincode reports a format mismatch.
+    Auto,
+}
+
+impl std::str::FromStr for DecodeFormat {
+    type Err = ();
+
+    /// Case-insensitive `bincode`, `postcard` or `auto`.
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "auto" => Ok(DecodeFormat::Auto),
+            "bincode" => Ok(DecodeFormat::Bincode),
+            "postcard" => Ok(DecodeFormat::Postcard),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Format set from `Config::qq_decode_format` at startup; `Auto` until then.
+static DECODE_FORMAT: OnceLock<DecodeFormat> = OnceLock::new();
+
+/// Sets the bytecode format used from now on. Only the first call has an effect.
+pub fn init_decode_format(format: DecodeFormat) {
+    DECODE_FORMAT.get_or_init(|| format);
+}
+
+/// Deserialize raw bytecode into the full Transaction, in the format set by `QQ_DECODE_FORMAT`.
+fn decode_transaction_bytes(bytes: &[u8]) -> Result<Transaction, QqDecodeError> {
+    decode_with_format(bytes, DECODE_FORMAT.get().copied().unwrap_or(DecodeFormat::Auto))
+}
+
+fn decode_with_format<T: DeserializeOwned>(bytes: &[u8], format: DecodeFormat) -> Result<T, QqDecodeError> {
+    match format {
+        DecodeFormat::Bincode => bincode::deserialize::<T>(bytes).map_err(QqDecodeError::Bincode),
+        DecodeFormat::Postcard => postcard::from_bytes::<T>(bytes).map_err(QqDecodeError::Postcard),
+        DecodeFormat::Auto => match bincode::deserialize::<T>(bytes) {
+            Ok(t) => Ok(t),
+            // This error pops up when bytes aren't from the expected format,
+            // e.g. postcard's varint enum tags read as bincode's u32 ones.
+            Err(e) if e.to_string().contains("expected variant index") => {
+                debug!("bincode format mismatch ({}), trying postcard", e);
+                postcard::from_bytes::<T>(bytes)
+                    .map_err(|postcard| QqDecodeError::BincodeFormatMismatch { bincode: e, postcard })
             }
+            Err(e) => Err(QqDecodeError::Bincode(e)),
+        },
+    }
+}
+
+/// Machine-readable cause of a failed decode, returned to API clients as `error_code`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeErrorCode {
+    /// The input is neither valid hex nor base64.
+    InvalidHex,
+    /// The bytes are not a bincode-encoded `Transaction`.
+    BincodeFailed,
+    /// The bytes are not a postcard-encoded `Transaction` either.
+    PostcardFailed,
+}
+
+impl DecodeErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DecodeErrorCode::InvalidHex => "INVALID_HEX",
+            DecodeErrorCode::BincodeFailed => "BINCODE_FAILED",
+            DecodeErrorCode::PostcardFailed => "POSTCARD_FAILED",
         }
     }
 }
@@ -41,38 +169,142 @@ pub enum DecodedQQTx {
     Message(Message),
 }
 
-pub fn decode_qq_transaction(tx_byte_code: &str, block_height: u64) -> Result<DecodedQQTx> {
-    println!("🔍 decode_qq_transaction: starting decode...");
+/// Variant name stored as `tx_type` in `qq_transactions`.
+fn tx_type_name(tx: &TransactionData) -> &'static str {
+    match tx {
+        TransactionData::TransactionTransfer(_) => "Transfer",
+        TransactionData::TransactionScript(_) => "Script",
+        TransactionData::Message(_) => "Message",
+    }
+}
+
+impl From<TransactionData> for DecodedQQTx {
+    fn from(tx: TransactionData) -> Self {
+        match tx {
+            TransactionData::TransactionTransfer(tx) => DecodedQQTx::Transfer(tx),
+            TransactionData::TransactionScript(tx)   => DecodedQQTx::Script(tx),
+            TransactionData::Message(msg)            => DecodedQQTx::Message(msg),
+        }
+    }
+}
+
+/// Decodes a QQ transaction for inspection. Nothing is stored; use
+/// `index_qq_transaction` when indexing a block.
+pub fn decode_qq_transaction(tx_byte_code: &str) -> Result<DecodedQQTx, QqDecodeError> {
+    Ok(decode_transaction(tx_byte_code)?.tx.into())
+}
 
-    let t = match decode_transaction(tx_byte_code) {
+/// The hash a QQ transaction is stored under in `qq_transactions`: SHA-256 of
+/// its bytecode, hex encoded.
+pub(crate) fn qq_tx_hash(tx_byte_code: &str) -> Result<String, QqDecodeError> {
+    Ok(hex::encode(Sha256::digest(decode_str_to_bytes(tx_byte_code)?)))
+}
+
+/// Decodes a QQ transaction and stores it in `qq_transactions`.
+///
+/// Also returns whether the transaction was newly stored. `false` means the same
+/// bytecode was already indexed (e.g. the height is being reprocessed), so
+/// callers should skip stats side effects.
+pub fn index_qq_transaction(writer: &mut dyn StatsWriter, tx_byte_code: &str, block_height: u64) -> Result<(DecodedQQTx, bool)> {
+    debug!("index_qq_transaction: starting decode...");
+
+    let bytes = decode_str_to_bytes(tx_byte_code)?;
+    let tx_hash = hex::encode(Sha256::digest(&bytes));
+
+    let t = match decode_transaction_bytes(&bytes) {
         Ok(t) => {
-            println!("🔍 decode_qq_transaction: decode_transaction succeeded, tx_type: {:?}",
-                match &t.tx {
-                    TransactionData::TransactionTransfer(_) => "Transfer",
-                    TransactionData::TransactionScript(_) => "Script",
-                    TransactionData::Message(_) => "Message",
-                });
+            debug!("index_qq_transaction: decode_transaction succeeded, tx_type: {:?}", tx_type_name(&t.tx));
             t
         },
         Err(e) => {
-            eprintln!("⚠️ decode_qq_transaction: decode_transaction failed: {:?}", e);
-            return Err(e);
+            warn!("index_qq_transaction: decode_transaction failed: {:?}", e);
+            return Err(e.into());
         }
     };
 
     let ts_json = serde_json::to_string_pretty(&t)
         .context("Failed to serialize Transaction to JSON")?;
 
-    println!("🔍 decode_qq_transaction: inserting to qq_tx table...");
-    if let Err(e) = insert_qq_tx(&ts_json, block_height) {
-        eprintln!("⚠️ decode_qq_transaction: insert_qq_tx failed: {:?}", e);
-        return Err(e.into());
+    debug!("index_qq_transaction: inserting to qq_transactions table...");
+    let is_new = match writer.insert_qq_tx(&tx_hash, tx_type_name(&t.tx), &ts_json, block_height) {
+        Ok(Some(id)) => {
+            debug!("index_qq_transaction: insert succeeded, id: {}", id);
+            true
+        }
+        Ok(None) => {
+            debug!("index_qq_transaction: tx {} already stored", tx_hash);
+            false
+        }
+        Err(e) => {
+            warn!("index_qq_transaction: insert_qq_tx failed: {:?}", e);
+            return Err(e);
+        }
+    };
+
+    Ok((t.tx.into(), is_new))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_str_to_bytes_accepts_hex_and_base64() {
+        let bytes = vec![0xfb, 0xff, 0xbf, 0x01];
+        assert_eq!(decode_str_to_bytes("0xfbffbf01").unwrap(), bytes);
+        assert_eq!(decode_str_to_bytes(" fbffbf01\n").unwrap(), bytes);
+        assert_eq!(decode_str_to_bytes("+/+/AQ==").unwrap(), bytes);
+        assert_eq!(decode_str_to_bytes("-_-_AQ==").unwrap(), bytes);
+        assert!(decode_str_to_bytes("0x+/+/AQ==").is_err());
+        assert!(decode_str_to_bytes("not hex or base64!").is_err());
+    }
+
+    #[test]
+    fn decode_errors_carry_a_code() {
+        let code = |input: &str| decode_transaction(input).unwrap_err().code();
+        assert_eq!(code("0xnot-hex"), DecodeErrorCode::InvalidHex);
+        assert_eq!(code("ab!c"), DecodeErrorCode::InvalidHex);
+        assert_eq!(code("0x00"), DecodeErrorCode::BincodeFailed);
+        assert_eq!(code("ffffffff"), DecodeErrorCode::BincodeFailed);
+        assert!(matches!(decode_transaction("0xnot-hex").unwrap_err(), QqDecodeError::HexDecode(_)));
+        assert!(matches!(decode_transaction("ab!c").unwrap_err(), QqDecodeError::Base64Decode(_)));
+        // The pure decoder reports the same errors
+        assert_eq!(decode_qq_transaction("0x00").unwrap_err().code(), DecodeErrorCode::BincodeFailed);
+    }
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    enum Sample {
+        Fee(u64),
+        Memo(String),
+    }
+
+    #[test]
+    fn postcard_payload_falls_back_in_auto_mode() {
+        let sample = Sample::Memo("qq".to_string());
+        let bytes = postcard::to_stdvec(&sample).unwrap();
+
+        let bincode_err = decode_with_format::<Sample>(&bytes, DecodeFormat::Bincode).unwrap_err();
+        assert!(matches!(bincode_err, QqDecodeError::Bincode(_)));
+        assert_eq!(decode_with_format::<Sample>(&bytes, DecodeFormat::Auto).unwrap(), sample);
+        assert_eq!(decode_with_format::<Sample>(&bytes, DecodeFormat::Postcard).unwrap(), sample);
+
+        let bincode_bytes = bincode::serialize(&Sample::Fee(7)).unwrap();
+        assert_eq!(decode_with_format::<Sample>(&bincode_bytes, DecodeFormat::Auto).unwrap(), Sample::Fee(7));
     }
-    println!("🔍 decode_qq_transaction: insert succeeded");
 
-    Ok(match t.tx {
-        TransactionData::TransactionTransfer(tx) => DecodedQQTx::Transfer(tx),
-        TransactionData::TransactionScript(tx)   => DecodedQQTx::Script(tx),
-        TransactionData::Message(msg)            => DecodedQQTx::Message(msg),
-    })
-}
\ No newline at end of file
+    #[test]
+    fn auto_mode_reports_postcard_failure() {
+        // bincode sees variant 0x0F0F0F0F, postcard a truncated varint
+        let err = decode_with_format::<Sample>(&[0x8f, 0x8f, 0x8f, 0x8f], DecodeFormat::Auto).unwrap_err();
+        assert!(matches!(err, QqDecodeError::BincodeFormatMismatch { .. }));
+        assert_eq!(err.code(), DecodeErrorCode::PostcardFailed);
+    }
+
+    #[test]
+    fn decode_format_parse_test() {
+        assert_eq!("auto".parse(), Ok(DecodeFormat::Auto));
+        assert_eq!("postcard".parse(), Ok(DecodeFormat::Postcard));
+        assert_eq!(" Bincode".parse(), Ok(DecodeFormat::Bincode));
+        assert_eq!("json".parse::<DecodeFormat>(), Err(()));
+    }
+}
@@ -0,0 +1,215 @@
+//! Per-client-IP token-bucket rate limiting for the REST API.
+//!
+//! Each client IP gets a bucket of `rate_limit_burst` tokens refilled at
+//! `rate_limit_rps` tokens per second (see `Config`); a request takes one token, and a
+//! request that finds the bucket empty gets `429 Too Many Requests` with a
+//! `Retry-After` header. IPv6 clients are keyed by their /64, which one client
+//! can rotate through freely. Health checks are never limited.
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{web, HttpResponse};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv6Addr};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::api::ErrorResponse;
+use crate::config::Config;
+
+/// Once this many clients are tracked, buckets that have refilled are dropped,
+/// then the least recently used if that isn't enough.
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Buckets evicted at once when every tracked client is still active, so the
+/// scan for the least recently used ones isn't repeated on every new client.
+const EVICT_BATCH: usize = MAX_TRACKED_CLIENTS / 10;
+
+/// Paths that are never limited (liveness and readiness probes).
+const UNLIMITED_PATHS: [&str; 3] = ["/api/health", "/api/health/", "/api/health/ready"];
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Token buckets keyed by client (see `client_key`).
+pub struct RateLimiter {
+    rate: f64,
+    burst: f64,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+impl RateLimiter {
+    /// `rate` tokens per second up to `burst` tokens per client.
+    pub fn new(rate: f64, burst: u32) -> Self {
+        RateLimiter {
+            rate,
+            burst: burst as f64,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Limiter configured by `RATE_LIMIT_RPS` / `RATE_LIMIT_BURST`, or `None` when limiting is off.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        (config.rate_limit_rps > 0.0).then(|| RateLimiter::new(config.rate_limit_rps, config.rate_limit_burst))
+    }
+
+    /// Takes a token for `ip` at `now`, or returns how long until one is available.
+    fn check(&self, ip: IpAddr, now: Instant) -> Result<(), Duration> {
+        let key = client_key(ip);
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() >= MAX_TRACKED_CLIENTS && !buckets.contains_key(&key) {
+            let full_after = Duration::from_secs_f64(self.burst / self.rate);
+            buckets.retain(|_, b| now.duration_since(b.refilled_at) < full_after);
+            if buckets.len() >= MAX_TRACKED_CLIENTS {
+                evict_least_recently_refilled(&mut buckets, EVICT_BATCH);
+            }
+        }
+
+        let bucket = buckets.entry(key).or_insert(Bucket { tokens: self.burst, refilled_at: now });
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate))
+        }
+    }
+}
+
+/// The bucket `ip` draws from: the address itself for IPv4, its /64 for IPv6
+/// (IPv4-mapped IPv6 addresses count as IPv4).
+fn client_key(ip: IpAddr) -> IpAddr {
+    match ip.to_canonical() {
+        IpAddr::V6(v6) => IpAddr::V6(Ipv6Addr::from(u128::from(v6) & !u128::from(u64::MAX))),
+        v4 => v4,
+    }
+}
+
+/// Drops the `count` buckets refilled longest ago (more if some were refilled at
+/// the same instant as the last of them).
+fn evict_least_recently_refilled(buckets: &mut HashMap<IpAddr, Bucket>, count: usize) {
+    let mut refilled: Vec<Instant> = buckets.values().map(|b| b.refilled_at).collect();
+    if count == 0 || refilled.is_empty() {
+        return;
+    }
+    let last = count.min(refilled.len()) - 1;
+    let cutoff = *refilled.select_nth_unstable(last).1;
+    buckets.retain(|_, b| b.refilled_at > cutoff);
+}
+
+/// Middleware (`middleware::from_fn`) applying the `RateLimiter` registered as
+/// app data. Requests pass through when no limiter is registered or the peer
+/// address is unknown.
+///
+/// Keyed by the socket peer address, not `X-Forwarded-For`, which clients can
+/// set freely; behind a reverse proxy every client shares the proxy's bucket.
+pub async fn limit_by_ip(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, actix_web::Error> {
+    let limiter = req.app_data::<web::Data<RateLimiter>>();
+    let peer = req.peer_addr().map(|addr| addr.ip());
+    if let (Some(limiter), Some(ip)) = (limiter, peer) {
+        if !UNLIMITED_PATHS.contains(&req.path()) {
+            if let Err(wait) = limiter.check(ip, Instant::now()) {
+                let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+                let response = HttpResponse::TooManyRequests()
+                    .insert_header(("Retry-After", retry_after.to_string()))
+                    .json(ErrorResponse {
+                        success: false,
+                        error: format!("Rate limit exceeded, retry in {} s", retry_after),
+                        error_code: None,
+                    });
+                return Ok(req.into_response(response).map_into_right_body());
+            }
+        }
+    }
+    next.call(req).await.map(ServiceResponse::map_into_left_body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::{middleware, test as actix_test, App};
+
+    #[test]
+    fn bucket_refills_at_the_configured_rate() {
+        let limiter = RateLimiter::new(2.0, 3);
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        let start = Instant::now();
+
+        assert!((0..3).all(|_| limiter.check(ip, start).is_ok()));
+        assert_eq!(limiter.check(ip, start), Err(Duration::from_millis(500)));
+        // Another client has its own bucket
+        assert!(limiter.check("10.0.0.2".parse().unwrap(), start).is_ok());
+        assert!(limiter.check(ip, start + Duration::from_millis(500)).is_ok());
+        assert!(limiter.check(ip, start + Duration::from_millis(500)).is_err());
+    }
+
+    #[test]
+    fn ipv6_clients_share_a_bucket_per_64() {
+        let limiter = RateLimiter::new(0.001, 1);
+        let start = Instant::now();
+
+        assert!(limiter.check("2001:db8:1:2::1".parse().unwrap(), start).is_ok());
+        assert!(limiter.check("2001:db8:1:2:ffff::9".parse().unwrap(), start).is_err());
+        assert!(limiter.check("2001:db8:1:3::1".parse().unwrap(), start).is_ok());
+        assert!(limiter.check("::ffff:10.0.0.1".parse().unwrap(), start).is_ok());
+        assert!(limiter.check("10.0.0.1".parse().unwrap(), start).is_err(), "IPv4-mapped addresses are IPv4 clients");
+    }
+
+    #[test]
+    fn active_clients_cannot_grow_the_map_past_the_cap() {
+        let limiter = RateLimiter::new(0.001, 5);
+        let start = Instant::now();
+        let first: IpAddr = "2001:db8::1".parse().unwrap();
+        assert!(limiter.check(first, start).is_ok());
+
+        // None of these buckets refill in time, so each new /64 is a new client
+        for i in 1..=MAX_TRACKED_CLIENTS as u128 {
+            let ip = IpAddr::V6(Ipv6Addr::from(u128::from(Ipv6Addr::LOCALHOST) | (i << 64)));
+            assert!(limiter.check(ip, start + Duration::from_millis(i as u64)).is_ok());
+        }
+
+        let buckets = limiter.buckets.lock().unwrap();
+        assert!(buckets.len() <= MAX_TRACKED_CLIENTS);
+        assert!(!buckets.contains_key(&client_key(first)), "the least recently used client is evicted first");
+    }
+
+    #[actix_web::test]
+    async fn requests_past_the_burst_get_429() {
+        let app = actix_test::init_service(
+            App::new()
+                .app_data(web::Data::new(RateLimiter::new(0.001, 2)))
+                .wrap(middleware::from_fn(limit_by_ip))
+                .route("/api/totals", web::get().to(HttpResponse::Ok))
+                .route("/api/health", web::get().to(HttpResponse::Ok))
+                .route("/api/healthz", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        let get = |path: &str| {
+            actix_test::TestRequest::get()
+                .uri(path)
+                .peer_addr("192.0.2.7:4000".parse().unwrap())
+                .to_request()
+        };
+
+        for _ in 0..2 {
+            assert_eq!(actix_test::call_service(&app, get("/api/totals")).await.status(), StatusCode::OK);
+        }
+        let limited = actix_test::call_service(&app, get("/api/totals")).await;
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(limited.headers().contains_key("Retry-After"));
+
+        assert_eq!(actix_test::call_service(&app, get("/api/health")).await.status(), StatusCode::OK);
+        let lookalike = actix_test::call_service(&app, get("/api/healthz")).await;
+        assert_eq!(lookalike.status(), StatusCode::TOO_MANY_REQUESTS, "only the health paths themselves are exempt");
+    }
+}
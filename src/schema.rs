@@ -16,6 +16,7 @@ diesel::table! {
     }
 }
 
+// Also indexed on q_address (migration 0015)
 diesel::table! {
     dark_burned_sats (t_address) {
         t_address -> Text,
@@ -26,6 +27,7 @@ diesel::table! {
     }
 }
 
+// Also indexed on q_address (migration 0015)
 diesel::table! {
     dark_minted_sats (t_address) {
         t_address -> Text,
@@ -74,11 +76,13 @@ diesel::table! {
 }
 
 diesel::table! {
-    qq_tx (tx_hash, block) {
-        tx_hash -> Text,
-        tx -> Text,
-        block -> BigInt,
+    qq_transactions (id) {
+        id -> Int4,
+        block_height -> BigInt,
+        tx_type -> Text,
+        tx_json -> Text,
         created_at -> Timestamp,
+        tx_hash -> Nullable<Text>,
     }
 }
 
@@ -107,4 +111,190 @@ diesel::table! {
         block -> BigInt,
         created_at -> Timestamp,
     }
-}
\ No newline at end of file
+}
+diesel::table! {
+    indexer_state (indexer_id) {
+        indexer_id -> Text,
+        last_height -> BigInt,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    fees_paid (t_address, denom) {
+        t_address -> Text,
+        denom -> Text,
+        amount -> BigInt,
+        block -> BigInt,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    ibc_transfers (t_address, receiver, denom, source_channel) {
+        t_address -> Text,
+        receiver -> Text,
+        denom -> Text,
+        amount -> BigInt,
+        source_channel -> Text,
+        block -> BigInt,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    script_txs (id) {
+        id -> Int4,
+        block_height -> BigInt,
+        kind -> Text,
+        from_address -> Nullable<Text>,
+        to_address -> Nullable<Text>,
+        script_json -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    block_hashes (height) {
+        height -> BigInt,
+        block_hash -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    height_deltas (id) {
+        id -> Int8,
+        block_height -> BigInt,
+        table_name -> Text,
+        t_address -> Text,
+        detail -> Nullable<Text>,
+        delta -> BigInt,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    unknown_msg_types (type_url) {
+        type_url -> Text,
+        count -> BigInt,
+        last_seen_height -> BigInt,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    block_times (height) {
+        height -> BigInt,
+        timestamp -> Timestamp,
+    }
+}
+
+diesel::table! {
+    skipped_blocks (height) {
+        height -> BigInt,
+        reason -> Text,
+        attempts -> Integer,
+        last_tried -> Timestamp,
+    }
+}
+
+diesel::table! {
+    global_totals (id) {
+        id -> Bool,
+        transaction_count -> BigInt,
+        address_count -> BigInt,
+        funds_moved -> BigInt,
+        lit_minted_sats -> BigInt,
+        lit_burned_sats -> BigInt,
+        dark_minted_sats -> BigInt,
+        dark_burned_sats -> BigInt,
+        refreshed_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    accounts (t_address) {
+        t_address -> Text,
+        pubkey_type -> Text,
+        pubkey_hex -> Nullable<Text>,
+        first_seen_height -> BigInt,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    btc_withdrawals (id) {
+        id -> Int4,
+        t_address -> Text,
+        withdraw_address -> Text,
+        reserve_id -> BigInt,
+        amount -> BigInt,
+        status -> Text,
+        requested_height -> BigInt,
+        btc_tx_hash -> Nullable<Text>,
+        confirmed_height -> Nullable<BigInt>,
+    }
+}
+
+diesel::table! {
+    btc_withdraw_confirmations (btc_tx_hash) {
+        btc_tx_hash -> Text,
+        btc_height -> BigInt,
+        btc_block_hash -> Text,
+        judge_address -> Text,
+        block -> BigInt,
+    }
+}
+
+diesel::table! {
+    btc_deposit_addresses (btc_deposit_address) {
+        btc_deposit_address -> Text,
+        twilight_address -> Text,
+        reserve_address -> Nullable<Text>,
+        height -> BigInt,
+    }
+}
+
+diesel::table! {
+    reserve_addresses (reserve_address) {
+        reserve_address -> Text,
+        reserve_script -> Text,
+        fragment_id -> BigInt,
+        judge_address -> Text,
+        registered_height -> BigInt,
+        updated_height -> BigInt,
+    }
+}
+
+diesel::table! {
+    sweep_events (id) {
+        id -> Int4,
+        reserve_id -> BigInt,
+        round_id -> BigInt,
+        kind -> Text,
+        actor -> Text,
+        fields -> Text,
+        block -> BigInt,
+    }
+}
+
+diesel::table! {
+    address_txs (t_address, qq_tx_id) {
+        t_address -> Text,
+        qq_tx_id -> Int4,
+        height -> BigInt,
+    }
+}
+
+diesel::table! {
+    indexed_blocks (height) {
+        height -> BigInt,
+        indexed_at -> Timestamp,
+    }
+}
+
+diesel::joinable!(address_txs -> qq_transactions (qq_tx_id));
+
+diesel::allow_tables_to_appear_in_same_query!(height_deltas, block_times);
+diesel::allow_tables_to_appear_in_same_query!(address_txs, qq_transactions);
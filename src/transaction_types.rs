@@ -1,11 +1,15 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use base64::engine::general_purpose::STANDARD as B64;
 use base64::Engine as _;
 use prost::Message;
 use prost_types::Any;
+use log::{debug, warn};
+use serde_json::{json, Value};
 
 // Tx containers from cosmos-sdk-proto
-use cosmos_sdk_proto::cosmos::tx::v1beta1::{AuthInfo, TxBody, TxRaw};
+use cosmos_sdk_proto::cosmos::base::v1beta1::Coin;
+use cosmos_sdk_proto::cosmos::tx::v1beta1::{AuthInfo, Fee, TxBody, TxRaw};
+use cosmos_sdk_proto::cosmos::crypto::{ed25519, secp256k1};
 
 // Common standard messages (add more as you need)
 use cosmos_sdk_proto::cosmos::bank::v1beta1::{MsgMultiSend, MsgSend, SendAuthorization};
@@ -15,18 +19,26 @@ use cosmos_sdk_proto::cosmos::distribution::v1beta1::{
     MsgWithdrawValidatorCommission,
 };
 use cosmos_sdk_proto::cosmos::gov::v1beta1::{MsgDeposit, MsgSubmitProposal, MsgVote, MsgVoteWeighted};
+use cosmos_sdk_proto::cosmos::gov::v1 as govV1;
+use cosmos_sdk_proto::cosmos::authz::v1beta1::{MsgExec, MsgGrant, MsgRevoke};
+use cosmos_sdk_proto::cosmos::feegrant::v1beta1::{MsgGrantAllowance, MsgRevokeAllowance};
 
-use twilight_indexer::twilightproject::nyks::bridge as nyksBridge;
-use twilight_indexer::twilightproject::nyks::zkos as nyksZkos;
+use crate::twilightproject::nyks::bridge as nyksBridge;
+use crate::twilightproject::nyks::zkos as nyksZkos;
+use crate::ibc::applications::transfer::v1::{Coin as IbcCoin, MsgTransfer};
 
 // Import upsert_transaction_count so it is available in this module
 use crate::db::*;
-use crate::quis_quis_tx::decode_qq_transaction;
+use crate::metrics;
+use crate::pubsub_chain::config;
+use crate::quis_quis_tx::{index_qq_transaction, qq_tx_hash};
 use crate::quis_quis_tx::DecodedQQTx;
+use transaction::ScriptTransaction;
 
 // use transaction::
 
-/// Typed envelope for standard Cosmos messages (no Debug/serde derives to avoid trait issues).
+/// Typed envelope for standard Cosmos messages. The cosmos-sdk-proto types have no serde
+/// derives, so use `to_json` for a JSON view.
 #[allow(dead_code)]
 #[derive(Debug)]
 pub enum StandardCosmosMsg {
@@ -53,6 +65,24 @@ pub enum StandardCosmosMsg {
     GovVote(MsgVote),
     GovVoteWeighted(MsgVoteWeighted),
 
+    // gov v1
+    GovV1SubmitProposal(govV1::MsgSubmitProposal),
+    GovV1Deposit(govV1::MsgDeposit),
+    GovV1Vote(govV1::MsgVote),
+    GovV1VoteWeighted(govV1::MsgVoteWeighted),
+
+    // ibc transfer
+    IbcTransfer(MsgTransfer),
+
+    // authz: the inner messages, decoded (and indexed) like top-level ones
+    AuthzExec { grantee: String, msgs: Vec<StandardCosmosMsg> },
+    AuthzGrant(MsgGrant),
+    AuthzRevoke(MsgRevoke),
+
+    // feegrant
+    FeegrantGrantAllowance(MsgGrantAllowance),
+    FeegrantRevokeAllowance(MsgRevokeAllowance),
+
     // ----- NEW: NYKS bridge custom msgs -----
     NyksConfirmBtcDeposit(nyksBridge::MsgConfirmBtcDeposit),
     NyksRegisterBtcDepositAddress(nyksBridge::MsgRegisterBtcDepositAddress),
@@ -75,6 +105,10 @@ pub enum StandardCosmosMsg {
     NyksZkosMsgMintBurnTradingBtc(nyksZkos::MsgMintBurnTradingBtc),
     /// Fallback
     Unknown { type_url: String, raw_value_hex: String },
+    /// A known (or unknown) message whose bytes could not be decoded; the rest of the tx is still indexed.
+    DecodeFailed { type_url: String, error: String },
+    /// A message of a `SKIP_TYPE_URLS` type, left undecoded by the indexer.
+    Skipped { type_url: String },
 }
 
 /// Final decoded transaction: concrete prost structs (no serde).
@@ -86,6 +120,290 @@ pub struct DecodedTx {
     pub _messages: Vec<StandardCosmosMsg>,
 }
 
+fn coin_json(coin: &Coin) -> Value {
+    json!({ "denom": coin.denom, "amount": coin.amount })
+}
+
+fn coins_json(coins: &[Coin]) -> Value {
+    Value::Array(coins.iter().map(coin_json).collect())
+}
+
+/// A nested `Any` we don't decode further (proposal content, gov v1 messages).
+fn any_json(any: &Any) -> Value {
+    json!({ "type_url": any.type_url, "value_hex": hex::encode(&any.value) })
+}
+
+/// Our own protos derive `Serialize` (see build.rs), so they serialize directly.
+fn proto_json<T: serde::Serialize>(msg: &T) -> Value {
+    serde_json::to_value(msg).unwrap_or_else(|e| json!({ "error": e.to_string() }))
+}
+
+impl StandardCosmosMsg {
+    /// JSON projection of the message: `{"type_url": "/<proto name>", "value": {...}}`.
+    /// Cosmos SDK types have no serde support, so their fields are copied by hand.
+    pub fn to_json(&self) -> Value {
+        let value = match self {
+            StandardCosmosMsg::BankSend(m) => json!({
+                "from_address": m.from_address,
+                "to_address": m.to_address,
+                "amount": coins_json(&m.amount),
+            }),
+            StandardCosmosMsg::BankMultiSend(m) => json!({
+                "inputs": m.inputs.iter().map(|i| json!({ "address": i.address, "coins": coins_json(&i.coins) })).collect::<Vec<_>>(),
+                "outputs": m.outputs.iter().map(|o| json!({ "address": o.address, "coins": coins_json(&o.coins) })).collect::<Vec<_>>(),
+            }),
+            StandardCosmosMsg::BankSendAuth(m) => json!({
+                "spend_limit": coins_json(&m.spend_limit),
+                "allow_list": m.allow_list,
+            }),
+
+            StandardCosmosMsg::StakingDelegate(m) => json!({
+                "delegator_address": m.delegator_address,
+                "validator_address": m.validator_address,
+                "amount": m.amount.as_ref().map(coin_json),
+            }),
+            StandardCosmosMsg::StakingUndelegate(m) => json!({
+                "delegator_address": m.delegator_address,
+                "validator_address": m.validator_address,
+                "amount": m.amount.as_ref().map(coin_json),
+            }),
+            StandardCosmosMsg::StakingBeginRedelegate(m) => json!({
+                "delegator_address": m.delegator_address,
+                "validator_src_address": m.validator_src_address,
+                "validator_dst_address": m.validator_dst_address,
+                "amount": m.amount.as_ref().map(coin_json),
+            }),
+
+            StandardCosmosMsg::DistWithdrawDelegatorReward(m) => json!({
+                "delegator_address": m.delegator_address,
+                "validator_address": m.validator_address,
+            }),
+            StandardCosmosMsg::DistWithdrawValidatorCommission(m) => json!({
+                "validator_address": m.validator_address,
+            }),
+            StandardCosmosMsg::DistSetWithdrawAddress(m) => json!({
+                "delegator_address": m.delegator_address,
+                "withdraw_address": m.withdraw_address,
+            }),
+            StandardCosmosMsg::DistFundCommunityPool(m) => json!({
+                "amount": coins_json(&m.amount),
+                "depositor": m.depositor,
+            }),
+
+            StandardCosmosMsg::GovSubmitProposal(m) => json!({
+                "content": m.content.as_ref().map(any_json),
+                "initial_deposit": coins_json(&m.initial_deposit),
+                "proposer": m.proposer,
+            }),
+            StandardCosmosMsg::GovDeposit(m) => json!({
+                "proposal_id": m.proposal_id,
+                "depositor": m.depositor,
+                "amount": coins_json(&m.amount),
+            }),
+            StandardCosmosMsg::GovVote(m) => json!({
+                "proposal_id": m.proposal_id,
+                "voter": m.voter,
+                "option": m.option,
+            }),
+            StandardCosmosMsg::GovVoteWeighted(m) => json!({
+                "proposal_id": m.proposal_id,
+                "voter": m.voter,
+                "options": m.options.iter().map(|o| json!({ "option": o.option, "weight": o.weight })).collect::<Vec<_>>(),
+            }),
+            StandardCosmosMsg::GovV1SubmitProposal(m) => json!({
+                "messages": m.messages.iter().map(any_json).collect::<Vec<_>>(),
+                "initial_deposit": coins_json(&m.initial_deposit),
+                "proposer": m.proposer,
+                "metadata": m.metadata,
+                "title": m.title,
+                "summary": m.summary,
+                "expedited": m.expedited,
+            }),
+            StandardCosmosMsg::GovV1Deposit(m) => json!({
+                "proposal_id": m.proposal_id,
+                "depositor": m.depositor,
+                "amount": coins_json(&m.amount),
+            }),
+            StandardCosmosMsg::GovV1Vote(m) => json!({
+                "proposal_id": m.proposal_id,
+                "voter": m.voter,
+                "option": m.option,
+                "metadata": m.metadata,
+            }),
+            StandardCosmosMsg::GovV1VoteWeighted(m) => json!({
+                "proposal_id": m.proposal_id,
+                "voter": m.voter,
+                "options": m.options.iter().map(|o| json!({ "option": o.option, "weight": o.weight })).collect::<Vec<_>>(),
+                "metadata": m.metadata,
+            }),
+
+            StandardCosmosMsg::IbcTransfer(m) => proto_json(m),
+            StandardCosmosMsg::AuthzExec { grantee, msgs } => json!({
+                "grantee": grantee,
+                "msgs": msgs.iter().map(StandardCosmosMsg::to_json).collect::<Vec<_>>(),
+            }),
+            StandardCosmosMsg::AuthzGrant(m) => json!({
+                "granter": m.granter,
+                "grantee": m.grantee,
+                "grant": m.grant.as_ref().map(|g| json!({
+                    "authorization": g.authorization.as_ref().map(any_json),
+                    "expiration": g.expiration.as_ref().map(|t| json!({ "seconds": t.seconds, "nanos": t.nanos })),
+                })),
+            }),
+            StandardCosmosMsg::AuthzRevoke(m) => json!({
+                "granter": m.granter,
+                "grantee": m.grantee,
+                "msg_type_url": m.msg_type_url,
+            }),
+
+            StandardCosmosMsg::FeegrantGrantAllowance(m) => json!({
+                "granter": m.granter,
+                "grantee": m.grantee,
+                "allowance": m.allowance.as_ref().map(any_json),
+            }),
+            StandardCosmosMsg::FeegrantRevokeAllowance(m) => json!({
+                "granter": m.granter,
+                "grantee": m.grantee,
+            }),
+
+            StandardCosmosMsg::NyksConfirmBtcDeposit(m) => proto_json(m),
+            StandardCosmosMsg::NyksRegisterBtcDepositAddress(m) => proto_json(m),
+            StandardCosmosMsg::NyksRegisterReserveAddress(m) => proto_json(m),
+            StandardCosmosMsg::NyksBootstrapFragment(m) => proto_json(m),
+            StandardCosmosMsg::NyksWithdrawBtcRequest(m) => proto_json(m),
+            StandardCosmosMsg::NyksWithdrawTxSigned(m) => proto_json(m),
+            StandardCosmosMsg::NyksWithdrawTxFinal(m) => proto_json(m),
+            StandardCosmosMsg::NyksConfirmBtcWithdraw(m) => proto_json(m),
+            StandardCosmosMsg::NyksProposeSweepAddress(m) => proto_json(m),
+            StandardCosmosMsg::NyksUnsignedTxSweep(m) => proto_json(m),
+            StandardCosmosMsg::NyksUnsignedTxRefund(m) => proto_json(m),
+            StandardCosmosMsg::NyksSignRefund(m) => proto_json(m),
+            StandardCosmosMsg::NyksSignSweep(m) => proto_json(m),
+            StandardCosmosMsg::NyksBroadcastTxRefund(m) => proto_json(m),
+            StandardCosmosMsg::NyksBroadcastTxSweep(m) => proto_json(m),
+            StandardCosmosMsg::NyksSweepProposal(m) => proto_json(m),
+            StandardCosmosMsg::NyksZkosMsgTransferTx(m) => proto_json(m),
+            StandardCosmosMsg::NyksZkosMsgMintBurnTradingBtc(m) => proto_json(m),
+
+            // Unrecognised or undecodable messages keep their own type_url
+            StandardCosmosMsg::Unknown { type_url, raw_value_hex } => {
+                return json!({ "type_url": type_url, "value_hex": raw_value_hex });
+            }
+            StandardCosmosMsg::DecodeFailed { type_url, error } => {
+                return json!({ "type_url": type_url, "error": error });
+            }
+            StandardCosmosMsg::Skipped { type_url } => {
+                return json!({ "type_url": type_url, "skipped": true });
+            }
+        };
+        json!({ "type_url": format!("/{}", type_name(self)), "value": value })
+    }
+
+    /// Chain addresses named in the message (senders, receivers, delegators, validators,
+    /// granters, judges, oracles, ...), in field order. BTC addresses and QuisQuis accounts
+    /// are left out; `AuthzExec` includes its inner messages.
+    pub fn addresses(&self) -> Vec<&str> {
+        match self {
+            StandardCosmosMsg::BankSend(m) => vec![m.from_address.as_str(), m.to_address.as_str()],
+            StandardCosmosMsg::BankMultiSend(m) => m
+                .inputs
+                .iter()
+                .map(|i| i.address.as_str())
+                .chain(m.outputs.iter().map(|o| o.address.as_str()))
+                .collect(),
+            StandardCosmosMsg::BankSendAuth(m) => m.allow_list.iter().map(String::as_str).collect(),
+
+            StandardCosmosMsg::StakingDelegate(m) => vec![m.delegator_address.as_str(), m.validator_address.as_str()],
+            StandardCosmosMsg::StakingUndelegate(m) => vec![m.delegator_address.as_str(), m.validator_address.as_str()],
+            StandardCosmosMsg::StakingBeginRedelegate(m) => vec![
+                m.delegator_address.as_str(),
+                m.validator_src_address.as_str(),
+                m.validator_dst_address.as_str(),
+            ],
+
+            StandardCosmosMsg::DistWithdrawDelegatorReward(m) => {
+                vec![m.delegator_address.as_str(), m.validator_address.as_str()]
+            }
+            StandardCosmosMsg::DistWithdrawValidatorCommission(m) => vec![m.validator_address.as_str()],
+            StandardCosmosMsg::DistSetWithdrawAddress(m) => vec![m.delegator_address.as_str(), m.withdraw_address.as_str()],
+            StandardCosmosMsg::DistFundCommunityPool(m) => vec![m.depositor.as_str()],
+
+            StandardCosmosMsg::GovSubmitProposal(m) => vec![m.proposer.as_str()],
+            StandardCosmosMsg::GovDeposit(m) => vec![m.depositor.as_str()],
+            StandardCosmosMsg::GovVote(m) => vec![m.voter.as_str()],
+            StandardCosmosMsg::GovVoteWeighted(m) => vec![m.voter.as_str()],
+            StandardCosmosMsg::GovV1SubmitProposal(m) => vec![m.proposer.as_str()],
+            StandardCosmosMsg::GovV1Deposit(m) => vec![m.depositor.as_str()],
+            StandardCosmosMsg::GovV1Vote(m) => vec![m.voter.as_str()],
+            StandardCosmosMsg::GovV1VoteWeighted(m) => vec![m.voter.as_str()],
+
+            StandardCosmosMsg::IbcTransfer(m) => vec![m.sender.as_str(), m.receiver.as_str()],
+            StandardCosmosMsg::AuthzExec { grantee, msgs } => std::iter::once(grantee.as_str())
+                .chain(msgs.iter().flat_map(StandardCosmosMsg::addresses))
+                .collect(),
+            StandardCosmosMsg::AuthzGrant(m) => vec![m.granter.as_str(), m.grantee.as_str()],
+            StandardCosmosMsg::AuthzRevoke(m) => vec![m.granter.as_str(), m.grantee.as_str()],
+            StandardCosmosMsg::FeegrantGrantAllowance(m) => vec![m.granter.as_str(), m.grantee.as_str()],
+            StandardCosmosMsg::FeegrantRevokeAllowance(m) => vec![m.granter.as_str(), m.grantee.as_str()],
+
+            StandardCosmosMsg::NyksConfirmBtcDeposit(m) => {
+                vec![m.twilight_deposit_address.as_str(), m.oracle_address.as_str()]
+            }
+            StandardCosmosMsg::NyksRegisterBtcDepositAddress(m) => vec![m.twilight_address.as_str()],
+            StandardCosmosMsg::NyksRegisterReserveAddress(m) => vec![m.judge_address.as_str()],
+            StandardCosmosMsg::NyksBootstrapFragment(m) => vec![m.judge_address.as_str(), m.validator_address.as_str()],
+            StandardCosmosMsg::NyksWithdrawBtcRequest(m) => vec![m.twilight_address.as_str()],
+            StandardCosmosMsg::NyksWithdrawTxSigned(m) => vec![m.creator.as_str(), m.validator_address.as_str()],
+            StandardCosmosMsg::NyksWithdrawTxFinal(m) => vec![m.creator.as_str(), m.judge_address.as_str()],
+            StandardCosmosMsg::NyksConfirmBtcWithdraw(m) => vec![m.judge_address.as_str()],
+            StandardCosmosMsg::NyksProposeSweepAddress(m) => vec![m.judge_address.as_str()],
+            StandardCosmosMsg::NyksUnsignedTxSweep(m) => vec![m.judge_address.as_str()],
+            StandardCosmosMsg::NyksUnsignedTxRefund(m) => vec![m.judge_address.as_str()],
+            StandardCosmosMsg::NyksSignRefund(m) => vec![m.signer_address.as_str()],
+            StandardCosmosMsg::NyksSignSweep(m) => vec![m.signer_address.as_str()],
+            StandardCosmosMsg::NyksBroadcastTxRefund(m) => vec![m.judge_address.as_str()],
+            StandardCosmosMsg::NyksBroadcastTxSweep(m) => vec![m.judge_address.as_str()],
+            StandardCosmosMsg::NyksSweepProposal(m) => vec![m.judge_address.as_str(), m.oracle_address.as_str()],
+            StandardCosmosMsg::NyksZkosMsgTransferTx(m) => vec![m.zk_oracle_address.as_str()],
+            StandardCosmosMsg::NyksZkosMsgMintBurnTradingBtc(m) => vec![m.twilight_address.as_str()],
+
+            StandardCosmosMsg::Unknown { .. }
+            | StandardCosmosMsg::DecodeFailed { .. }
+            | StandardCosmosMsg::Skipped { .. } => Vec::new(),
+        }
+    }
+}
+
+impl DecodedTx {
+    /// Every address the tx's messages name (`StandardCosmosMsg::addresses`), deduped in
+    /// first-seen order, without empty fields.
+    pub fn involved_addresses(&self) -> Vec<String> {
+        let mut seen = std::collections::HashSet::new();
+        self._messages
+            .iter()
+            .flat_map(StandardCosmosMsg::addresses)
+            .filter(|address| !address.is_empty() && seen.insert(*address))
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// JSON view of the whole tx: memo, fee, hex signatures and each message's `to_json`.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "memo": self._body.memo,
+            "timeout_height": self._body.timeout_height,
+            "fee": self._auth_info.fee.as_ref().map(|fee| json!({
+                "amount": coins_json(&fee.amount),
+                "gas_limit": fee.gas_limit,
+                "payer": fee.payer,
+                "granter": fee.granter,
+            })),
+            "signatures": self._signatures.iter().map(hex::encode).collect::<Vec<_>>(),
+            "messages": self._messages.iter().map(StandardCosmosMsg::to_json).collect::<Vec<_>>(),
+        })
+    }
+}
+
 /// Extract signer address from a message's Any type (for gas tracking)
 fn extract_signer_from_any(any: &Any) -> Option<String> {
     let t = any.type_url.as_str();
@@ -127,6 +445,48 @@ fn extract_signer_from_any(any: &Any) -> Option<String> {
             return Some(tx.voter);
         }
     }
+    // cosmos.authz.v1beta1.MsgExec
+    if ty(t, "cosmos.authz.v1beta1.MsgExec") {
+        if let Ok(tx) = MsgExec::decode(bytes) {
+            return Some(tx.grantee);
+        }
+    }
+    // cosmos.authz.v1beta1.MsgGrant
+    if ty(t, "cosmos.authz.v1beta1.MsgGrant") {
+        if let Ok(tx) = MsgGrant::decode(bytes) {
+            return Some(tx.granter);
+        }
+    }
+    // cosmos.authz.v1beta1.MsgRevoke
+    if ty(t, "cosmos.authz.v1beta1.MsgRevoke") {
+        if let Ok(tx) = MsgRevoke::decode(bytes) {
+            return Some(tx.granter);
+        }
+    }
+    // cosmos.feegrant.v1beta1.MsgGrantAllowance
+    if ty(t, "cosmos.feegrant.v1beta1.MsgGrantAllowance") {
+        if let Ok(tx) = MsgGrantAllowance::decode(bytes) {
+            return Some(tx.granter);
+        }
+    }
+    // cosmos.feegrant.v1beta1.MsgRevokeAllowance
+    if ty(t, "cosmos.feegrant.v1beta1.MsgRevokeAllowance") {
+        if let Ok(tx) = MsgRevokeAllowance::decode(bytes) {
+            return Some(tx.granter);
+        }
+    }
+    // ibc.applications.transfer.v1.MsgTransfer
+    if ty(t, "ibc.applications.transfer.v1.MsgTransfer") {
+        if let Ok(tx) = MsgTransfer::decode(bytes) {
+            return Some(tx.sender);
+        }
+    }
+    // cosmos.gov.v1.MsgVote
+    if ty(t, "cosmos.gov.v1.MsgVote") {
+        if let Ok(tx) = govV1::MsgVote::decode(bytes) {
+            return Some(tx.voter);
+        }
+    }
     // twilightproject.nyks.bridge.MsgConfirmBtcDeposit
     if ty(t, "twilightproject.nyks.bridge.MsgConfirmBtcDeposit") {
         if let Ok(tx) = nyksBridge::MsgConfirmBtcDeposit::decode(bytes) {
@@ -149,8 +509,295 @@ fn extract_signer_from_any(any: &Any) -> Option<String> {
     None
 }
 
+/// A BTC transaction output: the address its script pays on each network, and the sats paid.
+type BtcOutput = (Vec<String>, i64);
+
+/// The txid and outputs of a withdrawal's BTC transaction (hex, as in
+/// `MsgWithdrawTxFinal.btcTx`). `None` (with a warning) if it doesn't parse.
+fn btc_payouts(raw_tx: &str) -> Option<(String, Vec<BtcOutput>)> {
+    use bitcoin::{Address, Network};
+
+    let tx: bitcoin::Transaction = match bitcoin::consensus::encode::deserialize_hex(raw_tx.trim()) {
+        Ok(tx) => tx,
+        Err(e) => {
+            warn!("Skipping withdrawal BTC transaction that doesn't parse: {}", e);
+            return None;
+        }
+    };
+    let outputs = tx
+        .output
+        .iter()
+        .filter_map(|out| {
+            let paid_to = [Network::Bitcoin, Network::Testnet, Network::Regtest]
+                .into_iter()
+                .filter_map(|network| Address::from_script(&out.script_pubkey, network).ok())
+                .map(|address| address.to_string())
+                .collect();
+            Some((paid_to, sats_amount(out.value.to_sat(), "withdrawal BTC output")?))
+        })
+        .collect();
+
+    Some((tx.compute_txid().to_string(), outputs))
+}
+
+/// A coin amount as `i64`, or `None` (with a warning) if it isn't a number or doesn't fit.
+///
+/// Cosmos amounts are arbitrary-precision integers, so a large denomination can
+/// exceed `i64`; skipping the coin beats recording a wrong amount.
+fn parse_coin_amount(coin: &impl CosmosCoin, context: &str) -> Option<i64> {
+    match coin.amount().parse::<i64>() {
+        Ok(amount) => Some(amount),
+        Err(e) => {
+            warn!("Skipping {} coin with unusable amount {:?} {}: {}", context, coin.amount(), coin.denom(), e);
+            None
+        }
+    }
+}
+
+/// A sats amount as `i64`, or `None` (with a warning) if it doesn't fit.
+///
+/// Transactions that failed on chain are decoded too, so an amount the chain
+/// would never accept can still turn up here; a wrapped negative total is worse
+/// than a missing one.
+fn sats_amount(sats: u64, context: &str) -> Option<i64> {
+    match i64::try_from(sats) {
+        Ok(amount) => Some(amount),
+        Err(e) => {
+            warn!("Skipping {} with unusable amount {} sats: {}", context, sats, e);
+            None
+        }
+    }
+}
+
+/// The SDK's `Coin`, or the IBC transfer proto's own copy of it.
+trait CosmosCoin {
+    fn amount(&self) -> &str;
+    fn denom(&self) -> &str;
+}
+
+impl CosmosCoin for Coin {
+    fn amount(&self) -> &str {
+        &self.amount
+    }
+    fn denom(&self) -> &str {
+        &self.denom
+    }
+}
+
+impl CosmosCoin for IbcCoin {
+    fn amount(&self) -> &str {
+        &self.amount
+    }
+    fn denom(&self) -> &str {
+        &self.denom
+    }
+}
+
+/// Sums each `MsgMultiSend` input's coins per (address, denom), in first-seen order.
+fn multi_send_input_totals(tx: &MsgMultiSend) -> Vec<(String, String, i64)> {
+    let mut totals: Vec<(String, String, i64)> = Vec::new();
+    for input in &tx.inputs {
+        for coin in &input.coins {
+            let Some(amount) = parse_coin_amount(coin, "MsgMultiSend") else {
+                continue;
+            };
+            match totals.iter_mut().find(|(addr, denom, _)| *addr == input.address && *denom == coin.denom) {
+                Some((_, _, total)) => match total.checked_add(amount) {
+                    Some(sum) => *total = sum,
+                    None => warn!("Skipping MsgMultiSend coin {} {} for {}: total overflows i64", coin.amount, coin.denom, input.address),
+                },
+                None => totals.push((input.address.clone(), coin.denom.clone(), amount)),
+            }
+        }
+    }
+    totals
+}
+
+/// Owner of a coin or memo output; state outputs have no single owner we track.
+fn output_owner(output: &zkvm::Output) -> Option<String> {
+    match &output.output {
+        zkvm::OutputData::Coin(coin) => Some(coin.owner.clone()),
+        zkvm::OutputData::Memo(memo) => Some(memo.owner.clone()),
+        _ => None,
+    }
+}
+
+/// Hex of the bincode-encoded account a transfer output converted to, or `None`
+/// (with a warning) if the conversion or encoding failed.
+fn qq_account_hex<A: serde::Serialize, E: std::fmt::Debug>(account: Result<A, E>) -> Option<String> {
+    let account = match account {
+        Ok(account) => account,
+        Err(e) => {
+            warn!("Failed to convert output to quisquis account: {:?}", e);
+            return None;
+        }
+    };
+    match bincode::serialize(&account) {
+        Ok(bytes) => Some(hex::encode(bytes)),
+        Err(e) => {
+            warn!("Failed to serialize quisquis account: {:?}", e);
+            None
+        }
+    }
+}
+
+/// QuisQuis accounts a script touches: every input owner, then every coin/memo
+/// output owner, without duplicates.
+fn script_accounts(script: &ScriptTransaction) -> Vec<String> {
+    let inputs = script.get_input_values();
+    let outputs = script.get_output_values();
+    let owners = inputs
+        .iter()
+        .filter_map(|input| input.as_owner_address().cloned())
+        .chain(outputs.iter().filter_map(output_owner));
+
+    let mut accounts: Vec<String> = Vec::new();
+    for owner in owners {
+        if !accounts.contains(&owner) {
+            accounts.push(owner);
+        }
+    }
+    accounts
+}
+
+/// Stores a script tx in `script_txs` and credits it to the Twilight address
+/// behind its first input: a transaction count, plus an address mapping for
+/// every account it touches.
+fn record_script_tx(writer: &mut dyn StatsWriter, script: &ScriptTransaction, kind: &str, block_height: u64) -> Result<()> {
+    let inputs = script.get_input_values();
+    let outputs = script.get_output_values();
+    let from_address = inputs.first().and_then(|input| input.as_owner_address().cloned());
+    let to_address = outputs.first().and_then(output_owner);
+
+    match serde_json::to_string(script) {
+        Ok(script_json) => {
+            writer.insert_script_tx(kind, from_address.as_deref(), to_address.as_deref(), &script_json, block_height)?;
+        }
+        Err(e) => warn!("Failed to serialize script tx: {:?}", e),
+    }
+
+    let Some(from_address) = from_address else {
+        return Ok(());
+    };
+    let Some(t_address) = writer.get_taddress_for_qaddress(&from_address)? else {
+        return Ok(());
+    };
+
+    writer.insert_transaction_count(&t_address, block_height)?;
+    for account in script_accounts(script) {
+        writer.insert_addr_mappings(&t_address, &account, block_height)?;
+    }
+    Ok(())
+}
+
+/// Credits a QuisQuis transfer whose first input owner (`input_owners[0]`) is
+/// the sender: the sender's new account (`sender_account`, from the first
+/// output) is mapped to the sender's Twilight address, each of `recipients`
+/// (owner address, account) for the other outputs is mapped to its owner's
+/// Twilight address, and every input owner with a known Twilight address gets
+/// a transaction count.
+///
+/// Returns `false`, recording nothing, if the sender has no known Twilight address.
+fn record_transfer(
+    writer: &mut dyn StatsWriter,
+    input_owners: &[String],
+    sender_account: &str,
+    recipients: &[(String, String)],
+    block_height: u64,
+) -> Result<bool> {
+    let Some(sender) = input_owners.first() else {
+        return Ok(false);
+    };
+    let Some(t_address) = writer.get_taddress_for_qaddress(sender)? else {
+        return Ok(false);
+    };
+    writer.insert_addr_mappings(&t_address, sender_account, block_height)?;
+
+    for (owner, account) in recipients {
+        if let Some(recipient) = writer.get_taddress_for_qaddress(owner)? {
+            writer.insert_addr_mappings(&recipient, account, block_height)?;
+        }
+    }
+
+    writer.insert_transaction_count(&t_address, block_height)?;
+    for owner in &input_owners[1..] {
+        if let Some(t_address) = writer.get_taddress_for_qaddress(owner)? {
+            writer.insert_transaction_count(&t_address, block_height)?;
+        }
+    }
+    Ok(true)
+}
+
+/// Records gas usage against the signer and fees paid, per denom, against the fee payer.
+fn record_fee(writer: &mut dyn StatsWriter, fee: &Fee, signer_address: Option<&str>, block_height: u64) -> Result<()> {
+    if let (Some(coin), Some(addr)) = (fee.amount.first(), signer_address) {
+        if let Ok(gas_amount) = coin.amount.parse::<i64>() {
+            writer.insert_gas_used(addr, gas_amount, &coin.denom, block_height as i64)?;
+        }
+    }
+
+    if let Some(payer) = fee_payer(fee, signer_address) {
+        for coin in &fee.amount {
+            match coin.amount.parse::<i64>() {
+                Ok(fee_amount) => {
+                    writer.insert_fees_paid(&payer, &coin.denom, fee_amount, block_height)?;
+                }
+                Err(e) => warn!("Failed to parse fee amount {:?}: {:?}", coin.amount, e),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Address that pays the fee: `fee.granter` if set, then `fee.payer`, otherwise
+/// the first message's signer (the tx's first signer pays by default).
+fn fee_payer(fee: &Fee, signer_address: Option<&str>) -> Option<String> {
+    [fee.granter.as_str(), fee.payer.as_str()]
+        .into_iter()
+        .find(|addr| !addr.is_empty())
+        .or(signer_address)
+        .map(str::to_string)
+}
+
+/// Type (the key's type_url without the leading `/`) and hex-encoded bytes of the
+/// first signer's public key. Multisig and other key types come back with the type only.
+fn first_signer_pubkey(auth: &AuthInfo) -> Option<(String, Option<String>)> {
+    let key = auth.signer_infos.first()?.public_key.as_ref()?;
+    let t = key.type_url.as_str();
+    let bytes = key.value.as_slice();
+
+    let key_hex = if ty(t, "cosmos.crypto.secp256k1.PubKey") {
+        secp256k1::PubKey::decode(bytes).ok().map(|k| hex::encode(k.key))
+    } else if ty(t, "cosmos.crypto.ed25519.PubKey") {
+        ed25519::PubKey::decode(bytes).ok().map(|k| hex::encode(k.key))
+    } else {
+        None
+    };
+    Some((t.trim_start_matches('/').to_string(), key_hex))
+}
+
+/// Whether decoding a message also records its stats and metrics.
+enum Mode<'a> {
+    /// Indexing a block: every side effect goes through this writer.
+    Index(&'a mut dyn StatsWriter),
+    /// Decoding on request (e.g. from the API): nothing is written.
+    Inspect,
+}
+
 /// Decode a base64-encoded TxRaw (from `block.txs[i]`) into concrete structs.
-pub fn decode_tx_base64_standard(tx_b64: &str, block_height: u64) -> Result<DecodedTx> {
+///
+/// Stats are written through `writer`; a database error is returned as-is (not turned
+/// into `DecodeFailed`) so the caller can roll back the block.
+pub fn decode_tx_base64_standard(writer: &mut dyn StatsWriter, tx_b64: &str, block_height: u64) -> Result<DecodedTx> {
+    decode_tx(tx_b64, block_height, Mode::Index(writer))
+}
+
+/// Decode a base64-encoded TxRaw without touching the database or metrics.
+pub fn inspect_tx_base64_standard(tx_b64: &str) -> Result<DecodedTx> {
+    decode_tx(tx_b64, 0, Mode::Inspect)
+}
+
+fn decode_tx(tx_b64: &str, block_height: u64, mut mode: Mode) -> Result<DecodedTx> {
     // 1) base64 → bytes → TxRaw
     let raw_bytes = B64.decode(tx_b64.trim())?;
     let tx_raw = TxRaw::decode(raw_bytes.as_slice())?;
@@ -165,51 +812,213 @@ pub fn decode_tx_base64_standard(tx_b64: &str, block_height: u64) -> Result<Deco
     // 4) Messages (Any) → typed messages
     let mut msgs = Vec::<StandardCosmosMsg>::new();
     for any in &body.messages {
-        msgs.push(decode_standard_any(any, block_height)?);
+        msgs.push(decode_or_report(any, block_height, 0, &mut mode)?);
     }
 
-    // 5) Record gas usage if we have fee info and a signer address
-    if let (Some(fee), Some(addr)) = (&auth.fee, &signer_address) {
-        if let Some(coin) = fee.amount.first() {
-            if let Ok(gas_amount) = coin.amount.parse::<i64>() {
-                if let Err(e) = insert_gas_used(&addr, gas_amount, &coin.denom, block_height as i64) {
-                    eprintln!("⚠️ Failed to update gas_used_nyks for {}: {:?}", addr, e);
-                }
-            }
+    // 5) + 6) Gas used and fees paid
+    if let (Mode::Index(writer), Some(fee)) = (&mut mode, &auth.fee) {
+        record_fee(*writer, fee, signer_address.as_deref(), block_height)?;
+    }
+
+    // 7) The first signer's public key (the first message's signer is the first signer)
+    if let (Mode::Index(writer), Some(signer)) = (&mut mode, signer_address.as_deref()) {
+        if let Some((key_type, key_hex)) = first_signer_pubkey(&auth) {
+            writer.insert_account(signer, &key_type, key_hex.as_deref(), block_height)?;
         }
     }
 
-    Ok(DecodedTx {
+    let decoded = DecodedTx {
         _body: body,
         _auth_info: auth,
         _signatures: tx_raw.signatures, // raw bytes; hex when printing
         _messages: msgs,
-    })
+    };
+
+    // 8) The tx's addresses, linked to the QQ transactions it stored
+    if let Mode::Index(writer) = &mut mode {
+        record_address_txs(*writer, &decoded, block_height)?;
+    }
+
+    Ok(decoded)
 }
 
-pub fn decode_standard_any(any: &Any, block_height: u64) -> Result<StandardCosmosMsg> {
+/// Links every address the tx involves to each QQ transaction its `MsgTransferTx`s stored.
+fn record_address_txs(writer: &mut dyn StatsWriter, decoded: &DecodedTx, block_height: u64) -> Result<()> {
+    let mut addresses = None;
+    for msg in &decoded._messages {
+        let StandardCosmosMsg::NyksZkosMsgTransferTx(m) = msg else {
+            continue;
+        };
+        // Undecodable bytecode was never stored
+        let Ok(hash) = qq_tx_hash(&m.tx_byte_code) else {
+            continue;
+        };
+        let addresses = addresses.get_or_insert_with(|| decoded.involved_addresses());
+        writer.insert_address_txs(addresses, &hash, block_height)?;
+    }
+    Ok(())
+}
+
+/// Staking messages count as activity for the delegator.
+fn record_delegator_tx(writer: &mut dyn StatsWriter, delegator_address: &str, block_height: u64) -> Result<()> {
+    writer.insert_transaction_count(delegator_address, block_height)
+}
+
+/// Granting or revoking an authorization or fee allowance counts as activity for the granter.
+fn record_granter_tx(writer: &mut dyn StatsWriter, granter: &str, block_height: u64) -> Result<()> {
+    writer.insert_transaction_count(granter, block_height)
+}
+
+/// Records a sweep/refund message of reserve `reserve` in round `round`, sent by `sender`.
+fn record_sweep_event<T: serde::Serialize>(
+    mode: &mut Mode,
+    kind: SweepEventKind,
+    reserve: u64,
+    round: u64,
+    sender: &str,
+    msg: &T,
+    block_height: u64,
+) -> Result<()> {
+    if let Mode::Index(writer) = mode {
+        writer.insert_sweep_event(kind, reserve, round, sender, &proto_json(msg).to_string(), block_height)?;
+    }
+    Ok(())
+}
+
+/// How many `MsgExec` may be nested inside each other before decoding gives up.
+const MAX_EXEC_DEPTH: usize = 4;
+
+/// Decodes one message and applies its stats side effects, counting it per type in the metrics.
+/// Types listed in `SKIP_TYPE_URLS` are only counted, and come back as `Skipped`.
+pub fn decode_standard_any(writer: &mut dyn StatsWriter, any: &Any, block_height: u64) -> Result<StandardCosmosMsg> {
+    decode_nested_any(any, block_height, 0, &mut Mode::Index(writer))
+}
+
+/// `decode_standard_any` on a connection checked out of the pool, for one-off
+/// use outside block processing (tests, ad-hoc tools). Each call commits on its own.
+#[allow(dead_code)]
+pub fn decode_standard_any_pooled(any: &Any, block_height: u64) -> Result<StandardCosmosMsg> {
+    let mut conn = get_conn()?;
+    decode_standard_any(&mut PgWriter(&mut conn), any, block_height)
+}
+
+/// Decodes a message wrapped in `depth` levels of `MsgExec`.
+fn decode_nested_any(any: &Any, block_height: u64, depth: usize, mode: &mut Mode) -> Result<StandardCosmosMsg> {
+    if matches!(mode, Mode::Index(_)) && is_skipped_type(&any.type_url) {
+        // Same label the message would get if it were decoded
+        metrics::MESSAGES_DECODED_TOTAL.with_label_values(&[any.type_url.trim_start_matches('/')]).inc();
+        return Ok(StandardCosmosMsg::Skipped { type_url: any.type_url.clone() });
+    }
+    let msg = decode_any_message(any, block_height, depth, mode)?;
+    if matches!(mode, Mode::Index(_)) {
+        metrics::MESSAGES_DECODED_TOTAL.with_label_values(&[type_name(&msg)]).inc();
+    }
+    Ok(msg)
+}
+
+/// `decode_nested_any`, with a decode error reported as `DecodeFailed` so the
+/// messages around it keep their stats. Database errors are still returned so
+/// the caller can roll back the block.
+fn decode_or_report(any: &Any, block_height: u64, depth: usize, mode: &mut Mode) -> Result<StandardCosmosMsg> {
+    match decode_nested_any(any, block_height, depth, mode) {
+        Ok(msg) => Ok(msg),
+        Err(e) if is_db_error(&e) => Err(e),
+        Err(e) => {
+            if matches!(mode, Mode::Index(_)) {
+                metrics::DECODE_ERRORS_TOTAL.inc();
+            }
+            warn!("Failed to decode message {} at height {}: {:?}", any.type_url, block_height, e);
+            Ok(StandardCosmosMsg::DecodeFailed { type_url: any.type_url.clone(), error: e.to_string() })
+        }
+    }
+}
+
+/// Whether `type_url` is in `SKIP_TYPE_URLS`.
+fn is_skipped_type(type_url: &str) -> bool {
+    let type_url = type_url.trim_start_matches('/');
+    config().skip_type_urls.iter().any(|skip| skip == type_url)
+}
+
+fn decode_any_message(any: &Any, block_height: u64, depth: usize, mode: &mut Mode) -> Result<StandardCosmosMsg> {
     let t = any.type_url.as_str();
     let bytes = any.value.as_slice();
 
+    // ---------- cosmos.authz.v1beta1 ----------
+    if ty(t, "cosmos.authz.v1beta1.MsgExec") {
+        if depth >= MAX_EXEC_DEPTH {
+            bail!("MsgExec nested deeper than {} levels", MAX_EXEC_DEPTH);
+        }
+        let tx = MsgExec::decode(bytes)?;
+        // Inner messages that were already applied keep their stats, so a failed
+        // one is reported in its own slot rather than failing the whole MsgExec
+        let mut msgs = Vec::with_capacity(tx.msgs.len());
+        for inner in &tx.msgs {
+            msgs.push(decode_or_report(inner, block_height, depth + 1, mode)?);
+        }
+        return Ok(StandardCosmosMsg::AuthzExec { grantee: tx.grantee, msgs });
+    }
+    if ty(t, "cosmos.authz.v1beta1.MsgGrant") {
+        let tx = MsgGrant::decode(bytes)?;
+        if let Mode::Index(writer) = mode {
+            record_granter_tx(*writer, &tx.granter, block_height)?;
+        }
+        return Ok(StandardCosmosMsg::AuthzGrant(tx));
+    }
+    if ty(t, "cosmos.authz.v1beta1.MsgRevoke") {
+        let tx = MsgRevoke::decode(bytes)?;
+        if let Mode::Index(writer) = mode {
+            record_granter_tx(*writer, &tx.granter, block_height)?;
+        }
+        return Ok(StandardCosmosMsg::AuthzRevoke(tx));
+    }
+
+    // ---------- cosmos.feegrant.v1beta1 ----------
+    if ty(t, "cosmos.feegrant.v1beta1.MsgGrantAllowance") {
+        let tx = MsgGrantAllowance::decode(bytes)?;
+        if let Mode::Index(writer) = mode {
+            record_granter_tx(*writer, &tx.granter, block_height)?;
+        }
+        return Ok(StandardCosmosMsg::FeegrantGrantAllowance(tx));
+    }
+    if ty(t, "cosmos.feegrant.v1beta1.MsgRevokeAllowance") {
+        let tx = MsgRevokeAllowance::decode(bytes)?;
+        if let Mode::Index(writer) = mode {
+            record_granter_tx(*writer, &tx.granter, block_height)?;
+        }
+        return Ok(StandardCosmosMsg::FeegrantRevokeAllowance(tx));
+    }
+
     // ---------- cosmos.bank.v1beta1 ----------
     if ty(t, "cosmos.bank.v1beta1.MsgSend") {
         let tx = MsgSend::decode(bytes)?;
+        let Mode::Index(writer) = mode else {
+            return Ok(StandardCosmosMsg::BankSend(tx));
+        };
         
-        if let Err(e) = insert_transaction_count(&tx.from_address, block_height) {
-            eprintln!("⚠️ Failed to update transaction_count for {}: {:?}", tx.from_address, e);
-        }
+        writer.insert_transaction_count(&tx.from_address, block_height)?;
 
-        for coin in tx.amount.clone() {
-            let amount: i64 = coin.amount.parse::<i64>().expect("Failed to parse amount string to i64");
-            if let Err(e) = insert_funds_moved(&tx.to_address, amount, &coin.denom, block_height) {
-                eprintln!("⚠️ Failed to update funds_moved for {}: {:?}", tx.to_address, e);
+        for coin in &tx.amount {
+            if let Some(amount) = parse_coin_amount(coin, "MsgSend") {
+                writer.insert_funds_moved(&tx.to_address, amount, &coin.denom, block_height)?;
             }
         }
         return Ok(StandardCosmosMsg::BankSend(tx));
     }
 
     if ty(t, "cosmos.bank.v1beta1.MsgMultiSend") {
-        return Ok(StandardCosmosMsg::BankMultiSend(MsgMultiSend::decode(bytes)?));
+        let tx = MsgMultiSend::decode(bytes)?;
+        let Mode::Index(writer) = mode else {
+            return Ok(StandardCosmosMsg::BankMultiSend(tx));
+        };
+
+        for input in &tx.inputs {
+            writer.insert_transaction_count(&input.address, block_height)?;
+        }
+
+        for (address, denom, amount) in multi_send_input_totals(&tx) {
+            writer.insert_funds_moved(&address, amount, &denom, block_height)?;
+        }
+        return Ok(StandardCosmosMsg::BankMultiSend(tx));
     }
     if ty(t, "cosmos.bank.v1beta1.SendAuthorization") {
         return Ok(StandardCosmosMsg::BankSendAuth(SendAuthorization::decode(bytes)?));
@@ -217,13 +1026,25 @@ pub fn decode_standard_any(any: &Any, block_height: u64) -> Result<StandardCosmo
 
     // ---------- cosmos.staking.v1beta1 ----------
     if ty(t, "cosmos.staking.v1beta1.MsgDelegate") {
-        return Ok(StandardCosmosMsg::StakingDelegate(MsgDelegate::decode(bytes)?));
+        let tx = MsgDelegate::decode(bytes)?;
+        if let Mode::Index(writer) = mode {
+            record_delegator_tx(*writer, &tx.delegator_address, block_height)?;
+        }
+        return Ok(StandardCosmosMsg::StakingDelegate(tx));
     }
     if ty(t, "cosmos.staking.v1beta1.MsgUndelegate") {
-        return Ok(StandardCosmosMsg::StakingUndelegate(MsgUndelegate::decode(bytes)?));
+        let tx = MsgUndelegate::decode(bytes)?;
+        if let Mode::Index(writer) = mode {
+            record_delegator_tx(*writer, &tx.delegator_address, block_height)?;
+        }
+        return Ok(StandardCosmosMsg::StakingUndelegate(tx));
     }
     if ty(t, "cosmos.staking.v1beta1.MsgBeginRedelegate") {
-        return Ok(StandardCosmosMsg::StakingBeginRedelegate(MsgBeginRedelegate::decode(bytes)?));
+        let tx = MsgBeginRedelegate::decode(bytes)?;
+        if let Mode::Index(writer) = mode {
+            record_delegator_tx(*writer, &tx.delegator_address, block_height)?;
+        }
+        return Ok(StandardCosmosMsg::StakingBeginRedelegate(tx));
     }
 
     // ---------- cosmos.distribution.v1beta1 ----------
@@ -262,21 +1083,67 @@ pub fn decode_standard_any(any: &Any, block_height: u64) -> Result<StandardCosmo
         return Ok(StandardCosmosMsg::GovVoteWeighted(MsgVoteWeighted::decode(bytes)?));
     }
 
+    // ---------- cosmos.gov.v1 ----------
+    if ty(t, "cosmos.gov.v1.MsgSubmitProposal") {
+        return Ok(StandardCosmosMsg::GovV1SubmitProposal(govV1::MsgSubmitProposal::decode(bytes)?));
+    }
+    if ty(t, "cosmos.gov.v1.MsgDeposit") {
+        return Ok(StandardCosmosMsg::GovV1Deposit(govV1::MsgDeposit::decode(bytes)?));
+    }
+    if ty(t, "cosmos.gov.v1.MsgVote") {
+        return Ok(StandardCosmosMsg::GovV1Vote(govV1::MsgVote::decode(bytes)?));
+    }
+    if ty(t, "cosmos.gov.v1.MsgVoteWeighted") {
+        return Ok(StandardCosmosMsg::GovV1VoteWeighted(govV1::MsgVoteWeighted::decode(bytes)?));
+    }
+
+    // ---------- ibc.applications.transfer.v1 ----------
+    if ty(t, "ibc.applications.transfer.v1.MsgTransfer") {
+        let tx = MsgTransfer::decode(bytes)?;
+        let Mode::Index(writer) = mode else {
+            return Ok(StandardCosmosMsg::IbcTransfer(tx));
+        };
+
+        writer.insert_transaction_count(&tx.sender, block_height)?;
+
+        if let Some(token) = &tx.token {
+            if let Some(amount) = parse_coin_amount(token, "ibc transfer") {
+                writer.insert_funds_moved(&tx.sender, amount, &token.denom, block_height)?;
+                writer.insert_ibc_transfer(&tx.sender, &tx.receiver, &token.denom, amount, &tx.source_channel, block_height)?;
+            }
+        }
+        return Ok(StandardCosmosMsg::IbcTransfer(tx));
+    }
+
     // ---------- twilightproject.nyks.bridge (custom) ----------
     if ty(t, "twilightproject.nyks.bridge.MsgConfirmBtcDeposit") {
         let tx = nyksBridge::MsgConfirmBtcDeposit::decode(bytes)?;
+        let Mode::Index(writer) = mode else {
+            return Ok(StandardCosmosMsg::NyksConfirmBtcDeposit(tx));
+        };
+
+        let Some(deposit_amount) = sats_amount(tx.deposit_amount, "MsgConfirmBtcDeposit") else {
+            return Ok(StandardCosmosMsg::NyksConfirmBtcDeposit(tx));
+        };
+        writer.insert_lit_minted_sats(&tx.twilight_deposit_address, deposit_amount, block_height)?;
 
-        if let Err(e) = insert_lit_minted_sats(&tx.twilight_deposit_address, tx.deposit_amount as i64, block_height) {
-            eprintln!("⚠️ Failed to update transaction for {}: {:?}", tx.twilight_deposit_address, e);
-        }
-        
         return Ok(StandardCosmosMsg::NyksConfirmBtcDeposit(tx));
     }
     if ty(t, "twilightproject.nyks.bridge.MsgRegisterBtcDepositAddress") {
-        return Ok(StandardCosmosMsg::NyksRegisterBtcDepositAddress(nyksBridge::MsgRegisterBtcDepositAddress::decode(bytes)?));
+        let tx = nyksBridge::MsgRegisterBtcDepositAddress::decode(bytes)?;
+        let Mode::Index(writer) = mode else {
+            return Ok(StandardCosmosMsg::NyksRegisterBtcDepositAddress(tx));
+        };
+        writer.insert_btc_deposit_address(&tx.twilight_address, &tx.btc_deposit_address, block_height)?;
+        return Ok(StandardCosmosMsg::NyksRegisterBtcDepositAddress(tx));
     }
     if ty(t, "twilightproject.nyks.bridge.MsgRegisterReserveAddress") {
-        return Ok(StandardCosmosMsg::NyksRegisterReserveAddress(nyksBridge::MsgRegisterReserveAddress::decode(bytes)?));
+        let tx = nyksBridge::MsgRegisterReserveAddress::decode(bytes)?;
+        let Mode::Index(writer) = mode else {
+            return Ok(StandardCosmosMsg::NyksRegisterReserveAddress(tx));
+        };
+        writer.upsert_reserve_address(&tx.reserve_address, &tx.reserve_script, tx.fragment_id, &tx.judge_address, block_height)?;
+        return Ok(StandardCosmosMsg::NyksRegisterReserveAddress(tx));
     }
     if ty(t, "twilightproject.nyks.bridge.MsgBootstrapFragment") {
         return Ok(StandardCosmosMsg::NyksBootstrapFragment(nyksBridge::MsgBootstrapFragment::decode(bytes)?));
@@ -284,62 +1151,109 @@ pub fn decode_standard_any(any: &Any, block_height: u64) -> Result<StandardCosmo
 
     if ty(t, "twilightproject.nyks.bridge.MsgWithdrawBtcRequest") {
         let tx = nyksBridge::MsgWithdrawBtcRequest::decode(bytes)?;
-        if let Err(e) = insert_lit_burned_sats(&tx.twilight_address, tx.withdraw_amount as i64, block_height) {
-            eprintln!("⚠️ Failed to update transaction for {}: {:?}", tx.twilight_address, e);
-        }
+        let Mode::Index(writer) = mode else {
+            return Ok(StandardCosmosMsg::NyksWithdrawBtcRequest(tx));
+        };
+        let Some(withdraw_amount) = sats_amount(tx.withdraw_amount, "MsgWithdrawBtcRequest") else {
+            return Ok(StandardCosmosMsg::NyksWithdrawBtcRequest(tx));
+        };
+        writer.insert_lit_burned_sats(&tx.twilight_address, withdraw_amount, block_height)?;
+        writer.insert_btc_withdrawal_request(&tx.twilight_address, &tx.withdraw_address, tx.reserve_id, withdraw_amount, block_height)?;
         return Ok(StandardCosmosMsg::NyksWithdrawBtcRequest(tx));
     }
     if ty(t, "twilightproject.nyks.bridge.MsgWithdrawTxSigned") {
         return Ok(StandardCosmosMsg::NyksWithdrawTxSigned(nyksBridge::MsgWithdrawTxSigned::decode(bytes)?));
     }
     if ty(t, "twilightproject.nyks.bridge.MsgWithdrawTxFinal") {
-        return Ok(StandardCosmosMsg::NyksWithdrawTxFinal(nyksBridge::MsgWithdrawTxFinal::decode(bytes)?));
+        let tx = nyksBridge::MsgWithdrawTxFinal::decode(bytes)?;
+        let Mode::Index(writer) = mode else {
+            return Ok(StandardCosmosMsg::NyksWithdrawTxFinal(tx));
+        };
+        // Each output pays one request; a judge may already have confirmed the transaction
+        if let Some((txid, outputs)) = btc_payouts(&tx.btc_tx) {
+            for (paid_to, amount_sats) in &outputs {
+                writer.link_btc_withdrawal(paid_to, *amount_sats, &txid)?;
+            }
+            writer.confirm_btc_withdrawals(&txid)?;
+        }
+        return Ok(StandardCosmosMsg::NyksWithdrawTxFinal(tx));
     }
     if ty(t, "twilightproject.nyks.bridge.MsgConfirmBtcWithdraw") {
-        return Ok(StandardCosmosMsg::NyksConfirmBtcWithdraw(nyksBridge::MsgConfirmBtcWithdraw::decode(bytes)?));
+        let tx = nyksBridge::MsgConfirmBtcWithdraw::decode(bytes)?;
+        let Mode::Index(writer) = mode else {
+            return Ok(StandardCosmosMsg::NyksConfirmBtcWithdraw(tx));
+        };
+        // The requests it paid were linked by MsgWithdrawTxFinal
+        writer.record_btc_withdraw_confirmation(&tx.tx_hash, tx.height, &tx.hash, &tx.judge_address, block_height)?;
+        writer.confirm_btc_withdrawals(&tx.tx_hash)?;
+        return Ok(StandardCosmosMsg::NyksConfirmBtcWithdraw(tx));
     }
 
     if ty(t, "twilightproject.nyks.bridge.MsgProposeSweepAddress") {
-        return Ok(StandardCosmosMsg::NyksProposeSweepAddress(nyksBridge::MsgProposeSweepAddress::decode(bytes)?));
+        let tx = nyksBridge::MsgProposeSweepAddress::decode(bytes)?;
+        record_sweep_event(mode, SweepEventKind::ProposeSweepAddress, tx.reserve_id, tx.round_id, &tx.judge_address, &tx, block_height)?;
+        return Ok(StandardCosmosMsg::NyksProposeSweepAddress(tx));
     }
     if ty(t, "twilightproject.nyks.bridge.MsgUnsignedTxSweep") {
-        return Ok(StandardCosmosMsg::NyksUnsignedTxSweep(nyksBridge::MsgUnsignedTxSweep::decode(bytes)?));
+        let tx = nyksBridge::MsgUnsignedTxSweep::decode(bytes)?;
+        record_sweep_event(mode, SweepEventKind::UnsignedTxSweep, tx.reserve_id, tx.round_id, &tx.judge_address, &tx, block_height)?;
+        return Ok(StandardCosmosMsg::NyksUnsignedTxSweep(tx));
     }
     if ty(t, "twilightproject.nyks.bridge.MsgUnsignedTxRefund") {
-        return Ok(StandardCosmosMsg::NyksUnsignedTxRefund(nyksBridge::MsgUnsignedTxRefund::decode(bytes)?));
+        let tx = nyksBridge::MsgUnsignedTxRefund::decode(bytes)?;
+        record_sweep_event(mode, SweepEventKind::UnsignedTxRefund, tx.reserve_id, tx.round_id, &tx.judge_address, &tx, block_height)?;
+        return Ok(StandardCosmosMsg::NyksUnsignedTxRefund(tx));
     }
 
     if ty(t, "twilightproject.nyks.bridge.MsgSignRefund") {
-        return Ok(StandardCosmosMsg::NyksSignRefund(nyksBridge::MsgSignRefund::decode(bytes)?));
+        let tx = nyksBridge::MsgSignRefund::decode(bytes)?;
+        record_sweep_event(mode, SweepEventKind::SignRefund, tx.reserve_id, tx.round_id, &tx.signer_address, &tx, block_height)?;
+        return Ok(StandardCosmosMsg::NyksSignRefund(tx));
     }
     if ty(t, "twilightproject.nyks.bridge.MsgSignSweep") {
-        return Ok(StandardCosmosMsg::NyksSignSweep(nyksBridge::MsgSignSweep::decode(bytes)?));
+        let tx = nyksBridge::MsgSignSweep::decode(bytes)?;
+        record_sweep_event(mode, SweepEventKind::SignSweep, tx.reserve_id, tx.round_id, &tx.signer_address, &tx, block_height)?;
+        return Ok(StandardCosmosMsg::NyksSignSweep(tx));
     }
 
     if ty(t, "twilightproject.nyks.bridge.MsgBroadcastTxRefund") {
-        return Ok(StandardCosmosMsg::NyksBroadcastTxRefund(nyksBridge::MsgBroadcastTxRefund::decode(bytes)?));
+        let tx = nyksBridge::MsgBroadcastTxRefund::decode(bytes)?;
+        record_sweep_event(mode, SweepEventKind::BroadcastTxRefund, tx.reserve_id, tx.round_id, &tx.judge_address, &tx, block_height)?;
+        return Ok(StandardCosmosMsg::NyksBroadcastTxRefund(tx));
     }
     if ty(t, "twilightproject.nyks.bridge.MsgBroadcastTxSweep") {
-        return Ok(StandardCosmosMsg::NyksBroadcastTxSweep(nyksBridge::MsgBroadcastTxSweep::decode(bytes)?));
+        let tx = nyksBridge::MsgBroadcastTxSweep::decode(bytes)?;
+        record_sweep_event(mode, SweepEventKind::BroadcastTxSweep, tx.reserve_id, tx.round_id, &tx.judge_address, &tx, block_height)?;
+        return Ok(StandardCosmosMsg::NyksBroadcastTxSweep(tx));
     }
     if ty(t, "twilightproject.nyks.bridge.MsgSweepProposal") {
-        return Ok(StandardCosmosMsg::NyksSweepProposal(nyksBridge::MsgSweepProposal::decode(bytes)?));
+        let tx = nyksBridge::MsgSweepProposal::decode(bytes)?;
+        record_sweep_event(mode, SweepEventKind::SweepProposal, tx.reserve_id, tx.round_id, &tx.judge_address, &tx, block_height)?;
+        return Ok(StandardCosmosMsg::NyksSweepProposal(tx));
     }
 
     if ty(t, "twilightproject.nyks.zkos.MsgTransferTx") {
-        println!("🔍 Processing MsgTransferTx at block {}", block_height);
+        debug!("Processing MsgTransferTx at block {}", block_height);
         let cosmos_tx = nyksZkos::MsgTransferTx::decode(bytes)?;
-        println!("🔍 tx_byte_code length: {}", cosmos_tx.tx_byte_code.len());
+        let Mode::Index(writer) = mode else {
+            return Ok(StandardCosmosMsg::NyksZkosMsgTransferTx(cosmos_tx));
+        };
+        debug!("tx_byte_code length: {}", cosmos_tx.tx_byte_code.len());
 
-        let decoded = match decode_qq_transaction(&cosmos_tx.tx_byte_code, block_height) {
-            Ok(d) => d,
+        let decoded = match index_qq_transaction(*writer, &cosmos_tx.tx_byte_code, block_height) {
+            Ok((d, true)) => d,
+            Ok((_, false)) => {
+                debug!("QQ transaction already indexed, skipping stats updates");
+                return Ok(StandardCosmosMsg::NyksZkosMsgTransferTx(cosmos_tx));
+            }
+            Err(e) if is_db_error(&e) => return Err(e),
             Err(e) => {
-                eprintln!("⚠️ Failed to decode QQ transaction: {:?}", e);
+                warn!("Failed to decode QQ transaction: {:?}", e);
                 return Ok(StandardCosmosMsg::NyksZkosMsgTransferTx(cosmos_tx));
             }
         };
 
-        println!("🔍 Decoded QQ transaction type: {:?}", match &decoded {
+        debug!("Decoded QQ transaction type: {:?}", match &decoded {
             DecodedQQTx::Transfer(_) => "Transfer",
             DecodedQQTx::Script(_) => "Script",
             DecodedQQTx::Message(_) => "Message",
@@ -347,78 +1261,78 @@ pub fn decode_standard_any(any: &Any, block_height: u64) -> Result<StandardCosmo
 
         match decoded {
                 DecodedQQTx::Transfer(tx) => {
-                    println!("Got transfer tx: {:?}", tx);
+                    debug!("Got transfer tx: {:?}", tx);
                     let inputs = tx.get_input_values();
                     let outputs = tx.get_output_values();
                     if inputs.is_empty() || outputs.is_empty() { 
-                        eprintln!("⚠️ TransferTransaction has no inputs or outputs");
+                        warn!("TransferTransaction has no inputs or outputs");
                         return Ok(StandardCosmosMsg::NyksZkosMsgTransferTx(cosmos_tx));
                     }
                     let owner = match inputs[0].as_owner_address() {
                         Some(o) => o.clone(),
                         None => {
-                            eprintln!("⚠️ Failed to get owner address from input");
+                            warn!("Failed to get owner address from input");
                             return Ok(StandardCosmosMsg::NyksZkosMsgTransferTx(cosmos_tx))
                         },
                     };
-                    let new_qq_account = outputs[0]
-                        .to_quisquis_account()
-                        .expect("Failed to convert to quisquis account"
-                    );
-                    let new_qq_account = hex::encode(
-                    bincode::serialize(&new_qq_account)
-                        .expect("Failed to serialize account to bytes")
-                    );
-
-                    let t_address = match get_taddress_for_qaddress(&owner)?{
-                        Some(o) => o.clone(),
+                    let new_qq_account = match qq_account_hex(outputs[0].to_quisquis_account()) {
+                        Some(account) => account,
                         None => return Ok(StandardCosmosMsg::NyksZkosMsgTransferTx(cosmos_tx)),
                     };
 
-                    if let Err(e) = insert_addr_mappings(&t_address, &new_qq_account, block_height) {
-                        eprintln!("⚠️ Failed to update addr_mappings for {} <-> {}: {:?}", t_address, new_qq_account, e);
-                    }
+                    let input_owners: Vec<String> = inputs.iter().filter_map(|input| input.as_owner_address().cloned()).collect();
+                    let recipients: Vec<(String, String)> = outputs[1..]
+                        .iter()
+                        .filter_map(|output| Some((output_owner(output)?, qq_account_hex(output.to_quisquis_account())?)))
+                        .collect();
 
-                    if let Err(e) = insert_transaction_count(&t_address, block_height) {
-                        eprintln!("⚠️ Failed to update transaction_count for {}: {:?}", t_address, e);
+                    if !record_transfer(*writer, &input_owners, &new_qq_account, &recipients, block_height)? {
+                        return Ok(StandardCosmosMsg::NyksZkosMsgTransferTx(cosmos_tx));
                     }
 
                     if inputs[0].in_type == zkvm::IOType::Coin && outputs[0].out_type == zkvm::IOType::Memo {
-                        if let Err(e) = insert_trading_tx(&new_qq_account, &owner, block_height){
-                            eprintln!("⚠️ Failed to update trading tx for {}: {:?}", new_qq_account, e);
-                        }
+                        writer.insert_trading_tx(&new_qq_account, &owner, block_height)?;
                     }
                 }
                 DecodedQQTx::Script(script_tx) => {
-                    println!("Got script tx: {:?}", script_tx);
+                    debug!("Got script tx: {:?}", script_tx);
                     let inputs = script_tx.get_input_values();
                     let outputs = script_tx.get_output_values();
 
-                    println!("🔍 Script TX - inputs count: {}, outputs count: {}", inputs.len(), outputs.len());
+                    debug!("Script TX - inputs count: {}, outputs count: {}", inputs.len(), outputs.len());
 
                     if inputs.is_empty() || outputs.is_empty() {
-                        eprintln!("⚠️ ScriptTransaction has no inputs or outputs");
+                        warn!("ScriptTransaction has no inputs or outputs");
                         return Ok(StandardCosmosMsg::NyksZkosMsgTransferTx(cosmos_tx));
                     }
 
-                    println!("🔍 Script TX - input[0].in_type: {:?}, output[0].out_type: {:?}",
+                    debug!("Script TX - input[0].in_type: {:?}, output[0].out_type: {:?}",
                              inputs[0].in_type, outputs[0].out_type);
 
                     let is_order_open = inputs[0].in_type == zkvm::IOType::Coin && outputs[0].out_type == zkvm::IOType::Memo;
                     let is_order_close = inputs[0].in_type == zkvm::IOType::Memo && outputs[0].out_type == zkvm::IOType::Coin;
 
-                    println!("🔍 Script TX - is_order_open: {}, is_order_close: {}", is_order_open, is_order_close);
+                    debug!("Script TX - is_order_open: {}, is_order_close: {}", is_order_open, is_order_close);
+
+                    let kind = if is_order_open {
+                        "order_open"
+                    } else if is_order_close {
+                        "order_close"
+                    } else {
+                        "other"
+                    };
+                    record_script_tx(*writer, &script_tx, kind, block_height)?;
 
                     if is_order_open {
                         // Order Open: Coin input -> Memo output
                         // Get owner from input (Coin), get destination from output (Memo owner)
                         let from_address = match inputs[0].as_owner_address() {
                             Some(o) => {
-                                println!("🔍 Order Open - from_address (input owner): {}", o);
+                                debug!("Order Open - from_address (input owner): {}", o);
                                 o.clone()
                             },
                             None => {
-                                eprintln!("⚠️ Failed to get owner address from input");
+                                warn!("Failed to get owner address from input");
                                 return Ok(StandardCosmosMsg::NyksZkosMsgTransferTx(cosmos_tx))
                             },
                         };
@@ -426,25 +1340,22 @@ pub fn decode_standard_any(any: &Any, block_height: u64) -> Result<StandardCosmo
                         // Get owner from output by matching on OutputData
                         let to_address = match &outputs[0].output {
                             zkvm::OutputData::Memo(memo) => {
-                                println!("🔍 Order Open - to_address (memo owner): {}", memo.owner);
+                                debug!("Order Open - to_address (memo owner): {}", memo.owner);
                                 memo.owner.clone()
                             },
                             zkvm::OutputData::Coin(coin) => {
-                                println!("🔍 Order Open - to_address (coin owner): {}", coin.owner);
+                                debug!("Order Open - to_address (coin owner): {}", coin.owner);
                                 coin.owner.clone()
                             },
                             _ => {
-                                eprintln!("⚠️ Failed to get owner address from output - unexpected type");
+                                warn!("Failed to get owner address from output - unexpected type");
                                 return Ok(StandardCosmosMsg::NyksZkosMsgTransferTx(cosmos_tx))
                             },
                         };
 
-                        println!("📝 Inserting order_open_tx: to={}, from={}, block={}", to_address, from_address, block_height);
-                        if let Err(e) = insert_order_open_tx(&to_address, &from_address, block_height){
-                            eprintln!("⚠️ Failed to insert order_open_tx: {:?}", e);
-                        } else {
-                            println!("✅ Successfully inserted order_open_tx");
-                        }
+                        debug!("Inserting order_open_tx: to={}, from={}, block={}", to_address, from_address, block_height);
+                        writer.insert_order_open_tx(&to_address, &from_address, block_height)?;
+                        debug!("Successfully inserted order_open_tx");
                     }
 
                     if is_order_close {
@@ -452,11 +1363,11 @@ pub fn decode_standard_any(any: &Any, block_height: u64) -> Result<StandardCosmo
                         // Get owner from input (Memo), get destination from output (Coin)
                         let from_address = match inputs[0].as_owner_address() {
                             Some(o) => {
-                                println!("🔍 Order Close - from_address (input owner): {}", o);
+                                debug!("Order Close - from_address (input owner): {}", o);
                                 o.clone()
                             },
                             None => {
-                                eprintln!("⚠️ Failed to get owner address from input");
+                                warn!("Failed to get owner address from input");
                                 return Ok(StandardCosmosMsg::NyksZkosMsgTransferTx(cosmos_tx))
                             },
                         };
@@ -464,33 +1375,30 @@ pub fn decode_standard_any(any: &Any, block_height: u64) -> Result<StandardCosmo
                         // Get owner from output by matching on OutputData
                         let to_address = match &outputs[0].output {
                             zkvm::OutputData::Coin(coin) => {
-                                println!("🔍 Order Close - to_address (coin owner): {}", coin.owner);
+                                debug!("Order Close - to_address (coin owner): {}", coin.owner);
                                 coin.owner.clone()
                             },
                             zkvm::OutputData::Memo(memo) => {
-                                println!("🔍 Order Close - to_address (memo owner): {}", memo.owner);
+                                debug!("Order Close - to_address (memo owner): {}", memo.owner);
                                 memo.owner.clone()
                             },
                             _ => {
-                                eprintln!("⚠️ Failed to get owner address from output - unexpected type");
+                                warn!("Failed to get owner address from output - unexpected type");
                                 return Ok(StandardCosmosMsg::NyksZkosMsgTransferTx(cosmos_tx))
                             },
                         };
 
-                        println!("📝 Inserting order_close_tx: to={}, from={}, block={}", to_address, from_address, block_height);
-                        if let Err(e) = insert_order_close_tx(&to_address, &from_address, block_height){
-                            eprintln!("⚠️ Failed to insert order_close_tx: {:?}", e);
-                        } else {
-                            println!("✅ Successfully inserted order_close_tx");
-                        }
+                        debug!("Inserting order_close_tx: to={}, from={}, block={}", to_address, from_address, block_height);
+                        writer.insert_order_close_tx(&to_address, &from_address, block_height)?;
+                        debug!("Successfully inserted order_close_tx");
                     }
 
                     if !is_order_open && !is_order_close {
-                        println!("⚠️ Script TX did not match order_open or order_close conditions");
+                        warn!("Script TX did not match order_open or order_close conditions");
                     }
                 }
                 DecodedQQTx::Message(msg) => {
-                    println!("Got message tx: {:?}", msg);
+                    debug!("Got message tx: {:?}", msg);
                 }
         }
         return Ok(StandardCosmosMsg::NyksZkosMsgTransferTx(cosmos_tx));
@@ -498,28 +1406,32 @@ pub fn decode_standard_any(any: &Any, block_height: u64) -> Result<StandardCosmo
 
     if ty(t, "twilightproject.nyks.zkos.MsgMintBurnTradingBtc") {
         let tx = nyksZkos::MsgMintBurnTradingBtc::decode(bytes)?;
+        let Mode::Index(writer) = mode else {
+            return Ok(StandardCosmosMsg::NyksZkosMsgMintBurnTradingBtc(tx));
+        };
+        let Some(btc_value) = sats_amount(tx.btc_value, "MsgMintBurnTradingBtc") else {
+            return Ok(StandardCosmosMsg::NyksZkosMsgMintBurnTradingBtc(tx));
+        };
         if tx.mint_or_burn == true {
-            if let Err(e) = insert_dark_minted_sats(&tx.twilight_address, &tx.qq_account, tx.btc_value as i64, block_height) {
-                eprintln!("⚠️ Failed to update dark minted sats for {}: {:?}", tx.twilight_address, e);
-            }
-            if let Err(e) = insert_addr_mappings(&tx.twilight_address, &tx.qq_account, block_height) {
-                eprintln!("⚠️ Failed to update addr_mappings for {} <-> {}: {:?}", tx.twilight_address, tx.qq_account, e);
-            }
+            writer.insert_dark_minted_sats(&tx.twilight_address, &tx.qq_account, btc_value, block_height)?;
         }
         else if tx.mint_or_burn == false {
-            if let Err(e) = insert_dark_burned_sats(&tx.twilight_address, &tx.qq_account, tx.btc_value as i64, block_height) {
-                eprintln!("⚠️ Failed to update dark burned sats for {}: {:?}", tx.twilight_address, e);
-            }
+            writer.insert_dark_burned_sats(&tx.twilight_address, &tx.qq_account, btc_value, block_height)?;
         }
+        // Both directions name the pair, so a burn whose mint we never saw still
+        // links the accounts. The mapping is history, not a balance: it stays once
+        // the account is burned down to zero.
+        writer.insert_addr_mappings(&tx.twilight_address, &tx.qq_account, block_height)?;
 
-        if let Err(e) = insert_transaction_count(&tx.twilight_address, block_height) {
-            eprintln!("⚠️ Failed to update transaction_count for {}: {:?}", tx.twilight_address, e);
-        }
+        writer.insert_transaction_count(&tx.twilight_address, block_height)?;
 
         return Ok(StandardCosmosMsg::NyksZkosMsgMintBurnTradingBtc(tx));
     }
 
     // ---------- Fallback ----------
+    if let Mode::Index(writer) = mode {
+        writer.record_unknown_type(t, block_height)?;
+    }
     Ok(StandardCosmosMsg::Unknown {
         type_url: any.type_url.clone(),
         raw_value_hex: hex::encode(&any.value),
@@ -571,60 +1483,1007 @@ pub fn decode_standard_any(any: &Any, block_height: u64) -> Result<StandardCosmo
 //     }
 // }
 
-// fn type_name(m: &StandardCosmosMsg) -> &'static str {
-//     match m {
-//         // ---- Cosmos standard ----
-//         StandardCosmosMsg::BankSend(_) => "cosmos.bank.v1beta1.MsgSend",
-//         StandardCosmosMsg::BankMultiSend(_) => "cosmos.bank.v1beta1.MsgMultiSend",
-//         StandardCosmosMsg::BankSendAuth(_) => "cosmos.bank.v1beta1.SendAuthorization",
+/// Fully qualified proto name of a decoded message (also used as the metrics label).
+pub fn type_name(m: &StandardCosmosMsg) -> &'static str {
+    match m {
+        // ---- Cosmos standard ----
+        StandardCosmosMsg::BankSend(_) => "cosmos.bank.v1beta1.MsgSend",
+        StandardCosmosMsg::BankMultiSend(_) => "cosmos.bank.v1beta1.MsgMultiSend",
+        StandardCosmosMsg::BankSendAuth(_) => "cosmos.bank.v1beta1.SendAuthorization",
+
+        StandardCosmosMsg::StakingDelegate(_) => "cosmos.staking.v1beta1.MsgDelegate",
+        StandardCosmosMsg::StakingUndelegate(_) => "cosmos.staking.v1beta1.MsgUndelegate",
+        StandardCosmosMsg::StakingBeginRedelegate(_) => "cosmos.staking.v1beta1.MsgBeginRedelegate",
+
+        StandardCosmosMsg::DistWithdrawDelegatorReward(_) => "cosmos.distribution.v1beta1.MsgWithdrawDelegatorReward",
+        StandardCosmosMsg::DistWithdrawValidatorCommission(_) => "cosmos.distribution.v1beta1.MsgWithdrawValidatorCommission",
+        StandardCosmosMsg::DistSetWithdrawAddress(_) => "cosmos.distribution.v1beta1.MsgSetWithdrawAddress",
+        StandardCosmosMsg::DistFundCommunityPool(_) => "cosmos.distribution.v1beta1.MsgFundCommunityPool",
+
+        StandardCosmosMsg::GovSubmitProposal(_) => "cosmos.gov.v1beta1.MsgSubmitProposal",
+        StandardCosmosMsg::GovDeposit(_) => "cosmos.gov.v1beta1.MsgDeposit",
+        StandardCosmosMsg::GovVote(_) => "cosmos.gov.v1beta1.MsgVote",
+        StandardCosmosMsg::GovVoteWeighted(_) => "cosmos.gov.v1beta1.MsgVoteWeighted",
+        StandardCosmosMsg::GovV1SubmitProposal(_) => "cosmos.gov.v1.MsgSubmitProposal",
+        StandardCosmosMsg::GovV1Deposit(_) => "cosmos.gov.v1.MsgDeposit",
+        StandardCosmosMsg::GovV1Vote(_) => "cosmos.gov.v1.MsgVote",
+        StandardCosmosMsg::GovV1VoteWeighted(_) => "cosmos.gov.v1.MsgVoteWeighted",
+        StandardCosmosMsg::IbcTransfer(_) => "ibc.applications.transfer.v1.MsgTransfer",
+        StandardCosmosMsg::AuthzExec { .. } => "cosmos.authz.v1beta1.MsgExec",
+        StandardCosmosMsg::AuthzGrant(_) => "cosmos.authz.v1beta1.MsgGrant",
+        StandardCosmosMsg::AuthzRevoke(_) => "cosmos.authz.v1beta1.MsgRevoke",
+        StandardCosmosMsg::FeegrantGrantAllowance(_) => "cosmos.feegrant.v1beta1.MsgGrantAllowance",
+        StandardCosmosMsg::FeegrantRevokeAllowance(_) => "cosmos.feegrant.v1beta1.MsgRevokeAllowance",
+
+        // ---- Twilight NYKS bridge ----
+        StandardCosmosMsg::NyksConfirmBtcDeposit(_) => "twilightproject.nyks.bridge.MsgConfirmBtcDeposit",
+        StandardCosmosMsg::NyksRegisterBtcDepositAddress(_) => "twilightproject.nyks.bridge.MsgRegisterBtcDepositAddress",
+        StandardCosmosMsg::NyksRegisterReserveAddress(_) => "twilightproject.nyks.bridge.MsgRegisterReserveAddress",
+        StandardCosmosMsg::NyksBootstrapFragment(_) => "twilightproject.nyks.bridge.MsgBootstrapFragment",
+
+        StandardCosmosMsg::NyksWithdrawBtcRequest(_) => "twilightproject.nyks.bridge.MsgWithdrawBtcRequest",
+        StandardCosmosMsg::NyksWithdrawTxSigned(_) => "twilightproject.nyks.bridge.MsgWithdrawTxSigned",
+        StandardCosmosMsg::NyksWithdrawTxFinal(_) => "twilightproject.nyks.bridge.MsgWithdrawTxFinal",
+        StandardCosmosMsg::NyksConfirmBtcWithdraw(_) => "twilightproject.nyks.bridge.MsgConfirmBtcWithdraw",
+
+        StandardCosmosMsg::NyksProposeSweepAddress(_) => "twilightproject.nyks.bridge.MsgProposeSweepAddress",
+        StandardCosmosMsg::NyksUnsignedTxSweep(_) => "twilightproject.nyks.bridge.MsgUnsignedTxSweep",
+        StandardCosmosMsg::NyksUnsignedTxRefund(_) => "twilightproject.nyks.bridge.MsgUnsignedTxRefund",
+
+        StandardCosmosMsg::NyksSignRefund(_) => "twilightproject.nyks.bridge.MsgSignRefund",
+        StandardCosmosMsg::NyksSignSweep(_) => "twilightproject.nyks.bridge.MsgSignSweep",
+
+        StandardCosmosMsg::NyksBroadcastTxRefund(_) => "twilightproject.nyks.bridge.MsgBroadcastTxRefund",
+        StandardCosmosMsg::NyksBroadcastTxSweep(_) => "twilightproject.nyks.bridge.MsgBroadcastTxSweep",
+
+        StandardCosmosMsg::NyksSweepProposal(_) => "twilightproject.nyks.bridge.MsgSweepProposal",
+
+        StandardCosmosMsg::NyksZkosMsgTransferTx(_) => "twilightproject.nyks.zkos.MsgTransferTx",
+        StandardCosmosMsg::NyksZkosMsgMintBurnTradingBtc(_) => "twilightproject.nyks.zkos.MsgMintBurnTradingBtc",
+
+        // ---- Fallback ----
+        StandardCosmosMsg::Unknown { .. } => "<UNKNOWN>",
+        StandardCosmosMsg::DecodeFailed { .. } => "<DECODE_FAILED>",
+        StandardCosmosMsg::Skipped { .. } => "<SKIPPED>",
+    }
+}
 
-//         StandardCosmosMsg::StakingDelegate(_) => "cosmos.staking.v1beta1.MsgDelegate",
-//         StandardCosmosMsg::StakingUndelegate(_) => "cosmos.staking.v1beta1.MsgUndelegate",
-//         StandardCosmosMsg::StakingBeginRedelegate(_) => "cosmos.staking.v1beta1.MsgBeginRedelegate",
 
-//         StandardCosmosMsg::DistWithdrawDelegatorReward(_) => "cosmos.distribution.v1beta1.MsgWithdrawDelegatorReward",
-//         StandardCosmosMsg::DistWithdrawValidatorCommission(_) => "cosmos.distribution.v1beta1.MsgWithdrawValidatorCommission",
-//         StandardCosmosMsg::DistSetWithdrawAddress(_) => "cosmos.distribution.v1beta1.MsgSetWithdrawAddress",
-//         StandardCosmosMsg::DistFundCommunityPool(_) => "cosmos.distribution.v1beta1.MsgFundCommunityPool",
+fn ty(t: &str, want: &str) -> bool {
+    // Accept both "/pkg.MsgType" and "pkg.MsgType"
+    t == want || t.strip_prefix('/') == Some(want)
+}
 
-//         StandardCosmosMsg::GovSubmitProposal(_) => "cosmos.gov.v1beta1.MsgSubmitProposal",
-//         StandardCosmosMsg::GovDeposit(_) => "cosmos.gov.v1beta1.MsgDeposit",
-//         StandardCosmosMsg::GovVote(_) => "cosmos.gov.v1beta1.MsgVote",
-//         StandardCosmosMsg::GovVoteWeighted(_) => "cosmos.gov.v1beta1.MsgVoteWeighted",
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmos_sdk_proto::cosmos::bank::v1beta1::{Input, Output};
+    use cosmos_sdk_proto::cosmos::authz::v1beta1::Grant;
+    use cosmos_sdk_proto::cosmos::tx::v1beta1::SignerInfo;
 
-//         // ---- Twilight NYKS bridge ----
-//         StandardCosmosMsg::NyksConfirmBtcDeposit(_) => "twilightproject.nyks.bridge.MsgConfirmBtcDeposit",
-//         StandardCosmosMsg::NyksRegisterBtcDepositAddress(_) => "twilightproject.nyks.bridge.MsgRegisterBtcDepositAddress",
-//         StandardCosmosMsg::NyksRegisterReserveAddress(_) => "twilightproject.nyks.bridge.MsgRegisterReserveAddress",
-//         StandardCosmosMsg::NyksBootstrapFragment(_) => "twilightproject.nyks.bridge.MsgBootstrapFragment",
+    fn coin(denom: &str, amount: &str) -> Coin {
+        Coin { denom: denom.to_string(), amount: amount.to_string() }
+    }
 
-//         StandardCosmosMsg::NyksWithdrawBtcRequest(_) => "twilightproject.nyks.bridge.MsgWithdrawBtcRequest",
-//         StandardCosmosMsg::NyksWithdrawTxSigned(_) => "twilightproject.nyks.bridge.MsgWithdrawTxSigned",
-//         StandardCosmosMsg::NyksWithdrawTxFinal(_) => "twilightproject.nyks.bridge.MsgWithdrawTxFinal",
-//         StandardCosmosMsg::NyksConfirmBtcWithdraw(_) => "twilightproject.nyks.bridge.MsgConfirmBtcWithdraw",
+    fn two_input_multi_send(from_a: &str, from_b: &str) -> MsgMultiSend {
+        MsgMultiSend {
+            inputs: vec![
+                Input { address: from_a.to_string(), coins: vec![coin("nyks", "100"), coin("nyks", "50")] },
+                Input { address: from_b.to_string(), coins: vec![coin("nyks", "7"), coin("sats", "3")] },
+            ],
+            outputs: vec![Output { address: "twilight1dest".to_string(), coins: vec![coin("nyks", "157"), coin("sats", "3")] }],
+        }
+    }
 
-//         StandardCosmosMsg::NyksProposeSweepAddress(_) => "twilightproject.nyks.bridge.MsgProposeSweepAddress",
-//         StandardCosmosMsg::NyksUnsignedTxSweep(_) => "twilightproject.nyks.bridge.MsgUnsignedTxSweep",
-//         StandardCosmosMsg::NyksUnsignedTxRefund(_) => "twilightproject.nyks.bridge.MsgUnsignedTxRefund",
+    #[test]
+    fn decodes_gov_v1_vote() {
+        let vote = govV1::MsgVote {
+            proposal_id: 7,
+            voter: "twilight1voter".to_string(),
+            option: 1,
+            metadata: "ipfs://meta".to_string(),
+        };
+        let any = Any { type_url: "/cosmos.gov.v1.MsgVote".to_string(), value: vote.encode_to_vec() };
 
-//         StandardCosmosMsg::NyksSignRefund(_) => "twilightproject.nyks.bridge.MsgSignRefund",
-//         StandardCosmosMsg::NyksSignSweep(_) => "twilightproject.nyks.bridge.MsgSignSweep",
+        match decode_nested_any(&any, 1, 0, &mut Mode::Inspect).unwrap() {
+            StandardCosmosMsg::GovV1Vote(decoded) => assert_eq!(decoded, vote),
+            other => panic!("expected GovV1Vote, got {}", type_name(&other)),
+        }
+        assert_eq!(extract_signer_from_any(&any), Some("twilight1voter".to_string()));
+    }
 
-//         StandardCosmosMsg::NyksBroadcastTxRefund(_) => "twilightproject.nyks.bridge.MsgBroadcastTxRefund",
-//         StandardCosmosMsg::NyksBroadcastTxSweep(_) => "twilightproject.nyks.bridge.MsgBroadcastTxSweep",
+    fn ibc_transfer_any(sender: &str) -> Any {
+        let msg = MsgTransfer {
+            source_port: "transfer".to_string(),
+            source_channel: "channel-0".to_string(),
+            token: Some(IbcCoin { denom: "nyks".to_string(), amount: "250".to_string() }),
+            sender: sender.to_string(),
+            receiver: "osmo1receiver".to_string(),
+            timeout_height: None,
+            timeout_timestamp: 1_700_000_000_000_000_000,
+            memo: String::new(),
+        };
+        Any { type_url: "/ibc.applications.transfer.v1.MsgTransfer".to_string(), value: msg.encode_to_vec() }
+    }
 
-//         StandardCosmosMsg::NyksSweepProposal(_) => "twilightproject.nyks.bridge.MsgSweepProposal",
+    #[test]
+    fn decodes_ibc_transfer() {
+        let any = ibc_transfer_any("twilight1ibcsender");
+        let decoded = MsgTransfer::decode(any.value.as_slice()).unwrap();
+        assert_eq!(decoded.source_channel, "channel-0");
+        assert_eq!(decoded.token.as_ref().map(|c| c.amount.as_str()), Some("250"));
+        assert_eq!(type_name(&StandardCosmosMsg::IbcTransfer(decoded)), "ibc.applications.transfer.v1.MsgTransfer");
+        assert_eq!(extract_signer_from_any(&any), Some("twilight1ibcsender".to_string()));
+    }
 
-//         StandardCosmosMsg::NyksZkosMsgTransferTx(_) => "twilightproject.nyks.zkos.MsgTransferTx",
-//         StandardCosmosMsg::NyksZkosMsgMintBurnTradingBtc(_) => "twilightproject.nyks.zkos.MsgMintBurnTradingBtc",
+    #[test]
+    #[ignore = "requires a Postgres instance at DATABASE_URL"]
+    fn ibc_transfer_updates_sender_stats() {
+        crate::db::run_migrations().expect("migrations");
+        let nonce = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let sender = format!("twilight1ibc{}", nonce);
+
+        let decoded = decode_standard_any_pooled(&ibc_transfer_any(&sender), 42).expect("decode");
+        assert!(matches!(decoded, StandardCosmosMsg::IbcTransfer(_)));
+        assert_eq!(get_transaction_count(&sender).unwrap(), 1);
+        assert_eq!(get_funds_moved_total(&sender).unwrap(), Some(250));
+    }
 
-//         // ---- Fallback ----
-//         StandardCosmosMsg::Unknown { .. } => "<UNKNOWN>",
-//     }
-// }
+    #[test]
+    fn ibc_transfer_records_sender_and_route() {
+        let mut writer = MemoryWriter::default();
+
+        decode_standard_any(&mut writer, &ibc_transfer_any("twilight1ibcsender"), 42).expect("decode");
+        assert_eq!(writer.transaction_count("twilight1ibcsender"), 1);
+        assert_eq!(writer.funds_moved_total("twilight1ibcsender"), Some(250));
+        let route = (
+            "twilight1ibcsender".to_string(),
+            "osmo1receiver".to_string(),
+            "nyks".to_string(),
+            "channel-0".to_string(),
+        );
+        assert_eq!(writer.ibc_transfers, [(route, 250)].into());
+    }
 
+    #[test]
+    #[ignore = "requires a Postgres instance at DATABASE_URL"]
+    fn dry_run_writes_nothing() {
+        crate::db::run_migrations().expect("migrations");
+        let nonce = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let sender = format!("twilight1ibcdry{}", nonce);
+
+        let mut conn = get_conn().unwrap();
+        let decoded = decode_standard_any(&mut DryRunWriter(&mut conn), &ibc_transfer_any(&sender), 42).expect("decode");
+        assert!(matches!(decoded, StandardCosmosMsg::IbcTransfer(_)));
+        assert_eq!(get_transaction_count(&sender).unwrap(), 0);
+        assert_eq!(get_funds_moved_total(&sender).unwrap(), None);
+    }
 
-fn ty(t: &str, want: &str) -> bool {
-    // Accept both "/pkg.MsgType" and "pkg.MsgType"
-    t == want || t.strip_prefix('/') == Some(want)
-}
\ No newline at end of file
+    #[test]
+    #[ignore = "requires a Postgres instance at DATABASE_URL"]
+    fn side_effects_roll_back_with_the_transaction() {
+        use diesel::Connection;
+        crate::db::run_migrations().expect("migrations");
+        let nonce = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let sender = format!("twilight1ibcrb{}", nonce);
+
+        let result = get_conn().unwrap().transaction(|conn| {
+            decode_standard_any(&mut PgWriter(conn), &ibc_transfer_any(&sender), 42)?;
+            assert_eq!(get_transaction_count(&sender).unwrap(), 0, "not visible before commit");
+            Err::<(), _>(anyhow::anyhow!("block failed"))
+        });
+        assert!(result.is_err());
+        assert_eq!(get_transaction_count(&sender).unwrap(), 0);
+        assert_eq!(get_funds_moved_total(&sender).unwrap(), None);
+    }
+
+    fn exec_any(msgs: Vec<Any>) -> Any {
+        let exec = MsgExec { grantee: "twilight1grantee".to_string(), msgs };
+        Any { type_url: "/cosmos.authz.v1beta1.MsgExec".to_string(), value: exec.encode_to_vec() }
+    }
+
+    #[test]
+    fn exec_nesting_is_limited() {
+        let vote = govV1::MsgVote { proposal_id: 1, voter: "twilight1voter".to_string(), option: 1, metadata: String::new() };
+        let mut any = Any { type_url: "/cosmos.gov.v1.MsgVote".to_string(), value: vote.encode_to_vec() };
+        for _ in 0..MAX_EXEC_DEPTH {
+            any = exec_any(vec![any]);
+        }
+        // The message MAX_EXEC_DEPTH MsgExecs down
+        let innermost = |any: &Any| {
+            let mut msg = decode_nested_any(any, 1, 0, &mut Mode::Inspect).expect("outer MsgExec decodes");
+            for _ in 0..MAX_EXEC_DEPTH {
+                msg = match msg {
+                    StandardCosmosMsg::AuthzExec { mut msgs, .. } => msgs.remove(0),
+                    other => panic!("expected AuthzExec, got {}", type_name(&other)),
+                };
+            }
+            msg
+        };
+        assert!(matches!(innermost(&any), StandardCosmosMsg::GovV1Vote(_)));
+        assert!(matches!(innermost(&exec_any(vec![any])), StandardCosmosMsg::DecodeFailed { .. }));
+    }
+
+    #[test]
+    fn failed_exec_message_keeps_the_others_stats() {
+        let send = MsgSend {
+            from_address: "twilight1execsender".to_string(),
+            to_address: "twilight1execreceiver".to_string(),
+            amount: vec![coin("nyks", "40")],
+        };
+        let any = exec_any(vec![
+            Any { type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(), value: send.encode_to_vec() },
+            Any { type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(), value: vec![0xff, 0xff, 0xff] },
+        ]);
+        let mut writer = MemoryWriter::default();
+
+        match decode_standard_any(&mut writer, &any, 42).expect("MsgExec decodes") {
+            StandardCosmosMsg::AuthzExec { msgs, .. } => {
+                assert!(matches!(msgs.as_slice(), [StandardCosmosMsg::BankSend(_), StandardCosmosMsg::DecodeFailed { .. }]))
+            }
+            other => panic!("expected AuthzExec, got {}", type_name(&other)),
+        }
+        assert_eq!(writer.transaction_count("twilight1execsender"), 1);
+        assert_eq!(writer.funds_moved_total("twilight1execreceiver"), Some(40));
+    }
+
+    #[test]
+    #[ignore = "requires a Postgres instance at DATABASE_URL"]
+    fn exec_indexes_inner_send() {
+        crate::db::run_migrations().expect("migrations");
+        let nonce = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let (from, to) = (format!("twilight1exfrom{}", nonce), format!("twilight1exto{}", nonce));
+        let send = MsgSend { from_address: from.clone(), to_address: to.clone(), amount: vec![coin("nyks", "40")] };
+        let any = exec_any(vec![Any { type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(), value: send.encode_to_vec() }]);
+
+        match decode_standard_any_pooled(&any, 42).expect("decode") {
+            StandardCosmosMsg::AuthzExec { grantee, msgs } => {
+                assert_eq!(grantee, "twilight1grantee");
+                assert!(matches!(msgs.as_slice(), [StandardCosmosMsg::BankSend(_)]));
+            }
+            other => panic!("expected AuthzExec, got {}", type_name(&other)),
+        }
+        assert_eq!(get_transaction_count(&from).unwrap(), 1);
+        assert_eq!(get_funds_moved_total(&to).unwrap(), Some(40));
+    }
+
+    #[test]
+    fn corrupt_message_does_not_drop_the_rest_of_the_tx() {
+        let vote = govV1::MsgVote { proposal_id: 3, voter: "twilight1voter".to_string(), option: 1, metadata: String::new() };
+        let body = TxBody {
+            messages: vec![
+                Any { type_url: "/cosmos.gov.v1.MsgVote".to_string(), value: vec![0xff, 0xff, 0xff] },
+                Any { type_url: "/cosmos.gov.v1.MsgVote".to_string(), value: vote.encode_to_vec() },
+            ],
+            ..Default::default()
+        };
+        let tx_raw = TxRaw {
+            body_bytes: body.encode_to_vec(),
+            auth_info_bytes: AuthInfo::default().encode_to_vec(),
+            signatures: vec![],
+        };
+
+        let decoded = inspect_tx_base64_standard(&B64.encode(tx_raw.encode_to_vec())).expect("tx decodes");
+        match decoded._messages.as_slice() {
+            [StandardCosmosMsg::DecodeFailed { type_url, .. }, StandardCosmosMsg::GovV1Vote(v)] => {
+                assert_eq!(type_url, "/cosmos.gov.v1.MsgVote");
+                assert_eq!(v.proposal_id, 3);
+            }
+            other => panic!("unexpected messages: {:?}", other),
+        }
+    }
+
+    fn signed_send_b64(public_key: Any) -> String {
+        let send = MsgSend {
+            from_address: "twilight1signer".to_string(),
+            to_address: "twilight1to".to_string(),
+            amount: vec![coin("nyks", "5")],
+        };
+        let body = TxBody {
+            messages: vec![Any { type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(), value: send.encode_to_vec() }],
+            ..Default::default()
+        };
+        let auth = AuthInfo {
+            signer_infos: vec![SignerInfo { public_key: Some(public_key), ..Default::default() }],
+            ..Default::default()
+        };
+        let tx_raw = TxRaw { body_bytes: body.encode_to_vec(), auth_info_bytes: auth.encode_to_vec(), signatures: vec![] };
+        B64.encode(tx_raw.encode_to_vec())
+    }
+
+    #[test]
+    fn first_signer_key_is_recorded_for_the_signer() {
+        let key = secp256k1::PubKey { key: vec![0x02, 0xab, 0xcd] };
+        let tx = signed_send_b64(Any { type_url: "/cosmos.crypto.secp256k1.PubKey".to_string(), value: key.encode_to_vec() });
+        let mut writer = MemoryWriter::default();
+
+        decode_tx_base64_standard(&mut writer, &tx, 30).expect("decode");
+        decode_tx_base64_standard(&mut writer, &tx, 20).expect("decode");
+        assert_eq!(
+            writer.accounts,
+            [(
+                "twilight1signer".to_string(),
+                ("cosmos.crypto.secp256k1.PubKey".to_string(), Some("02abcd".to_string()), 20)
+            )]
+            .into()
+        );
+    }
+
+    #[test]
+    fn multisig_signer_is_recorded_by_type_only() {
+        let tx = signed_send_b64(Any {
+            type_url: "/cosmos.crypto.multisig.LegacyAminoPubKey".to_string(),
+            value: vec![0x08, 0x02],
+        });
+        let mut writer = MemoryWriter::default();
+
+        decode_tx_base64_standard(&mut writer, &tx, 30).expect("decode");
+        assert_eq!(
+            writer.accounts.get("twilight1signer"),
+            Some(&("cosmos.crypto.multisig.LegacyAminoPubKey".to_string(), None, 30))
+        );
+    }
+
+    #[test]
+    fn inspect_decodes_to_json_without_indexing() {
+        // An unparseable amount would panic on the indexing path, which parses it for funds_moved
+        let send = MsgSend {
+            from_address: "twilight1from".to_string(),
+            to_address: "twilight1to".to_string(),
+            amount: vec![coin("nyks", "not-a-number")],
+        };
+        let body = TxBody {
+            messages: vec![
+                Any { type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(), value: send.encode_to_vec() },
+                Any { type_url: "/example.v1.MsgUnknown".to_string(), value: vec![0xab, 0xcd] },
+            ],
+            memo: "hello".to_string(),
+            ..Default::default()
+        };
+        let tx_raw = TxRaw {
+            body_bytes: body.encode_to_vec(),
+            auth_info_bytes: AuthInfo::default().encode_to_vec(),
+            signatures: vec![vec![0x01, 0x02]],
+        };
+
+        let tx = inspect_tx_base64_standard(&B64.encode(tx_raw.encode_to_vec())).expect("tx decodes").to_json();
+        assert_eq!(tx["memo"], "hello");
+        assert_eq!(tx["signatures"], json!(["0102"]));
+        assert_eq!(tx["messages"][0]["type_url"], "/cosmos.bank.v1beta1.MsgSend");
+        assert_eq!(tx["messages"][0]["value"]["amount"][0]["amount"], "not-a-number");
+        assert_eq!(tx["messages"][1], json!({ "type_url": "/example.v1.MsgUnknown", "value_hex": "abcd" }));
+    }
+
+    #[test]
+    fn bank_send_to_json() {
+        let msg = StandardCosmosMsg::BankSend(MsgSend {
+            from_address: "twilight1from".to_string(),
+            to_address: "twilight1to".to_string(),
+            amount: vec![coin("nyks", "10"), coin("sats", "2")],
+        });
+        assert_eq!(
+            msg.to_json(),
+            json!({
+                "type_url": "/cosmos.bank.v1beta1.MsgSend",
+                "value": {
+                    "from_address": "twilight1from",
+                    "to_address": "twilight1to",
+                    "amount": [{ "denom": "nyks", "amount": "10" }, { "denom": "sats", "amount": "2" }],
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn staking_to_json() {
+        let delegate = StandardCosmosMsg::StakingDelegate(MsgDelegate {
+            delegator_address: "twilight1del".to_string(),
+            validator_address: "twilightvaloper1val".to_string(),
+            amount: Some(coin("nyks", "500")),
+        });
+        assert_eq!(
+            delegate.to_json(),
+            json!({
+                "type_url": "/cosmos.staking.v1beta1.MsgDelegate",
+                "value": {
+                    "delegator_address": "twilight1del",
+                    "validator_address": "twilightvaloper1val",
+                    "amount": { "denom": "nyks", "amount": "500" },
+                },
+            })
+        );
+
+        let redelegate = StandardCosmosMsg::StakingBeginRedelegate(MsgBeginRedelegate {
+            delegator_address: "twilight1del".to_string(),
+            validator_src_address: "twilightvaloper1src".to_string(),
+            validator_dst_address: "twilightvaloper1dst".to_string(),
+            amount: None,
+        });
+        let json = redelegate.to_json();
+        assert_eq!(json["type_url"], "/cosmos.staking.v1beta1.MsgBeginRedelegate");
+        assert_eq!(json["value"]["validator_dst_address"], "twilightvaloper1dst");
+        assert!(json["value"]["amount"].is_null());
+    }
+
+    #[test]
+    fn nyks_bridge_to_json() {
+        let msg = StandardCosmosMsg::NyksConfirmBtcDeposit(nyksBridge::MsgConfirmBtcDeposit {
+            reserve_address: "bc1reserve".to_string(),
+            deposit_amount: 50_000,
+            height: 840_000,
+            hash: "ab12".to_string(),
+            twilight_deposit_address: "twilight1dep".to_string(),
+            oracle_address: "twilight1oracle".to_string(),
+        });
+        assert_eq!(
+            msg.to_json(),
+            json!({
+                "type_url": "/twilightproject.nyks.bridge.MsgConfirmBtcDeposit",
+                "value": {
+                    "reserve_address": "bc1reserve",
+                    "deposit_amount": 50_000,
+                    "height": 840_000,
+                    "hash": "ab12",
+                    "twilight_deposit_address": "twilight1dep",
+                    "oracle_address": "twilight1oracle",
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn grant_counts_for_the_granter() {
+        let send_auth = SendAuthorization { spend_limit: vec![coin("nyks", "500")], allow_list: vec![] };
+        let grant = MsgGrant {
+            granter: "twilight1granter".to_string(),
+            grantee: "twilight1grantee".to_string(),
+            grant: Some(Grant {
+                authorization: Some(Any {
+                    type_url: "/cosmos.bank.v1beta1.SendAuthorization".to_string(),
+                    value: send_auth.encode_to_vec(),
+                }),
+                expiration: None,
+            }),
+        };
+        let any = Any { type_url: "/cosmos.authz.v1beta1.MsgGrant".to_string(), value: grant.encode_to_vec() };
+        let mut writer = MemoryWriter::default();
+
+        let msg = decode_standard_any(&mut writer, &any, 9).expect("decode");
+        assert!(matches!(&msg, StandardCosmosMsg::AuthzGrant(m) if m.grantee == "twilight1grantee"));
+        assert_eq!(writer.transactions, [("twilight1granter".to_string(), 9)].into());
+        assert_eq!(extract_signer_from_any(&any), Some("twilight1granter".to_string()));
+
+        let json = msg.to_json();
+        assert_eq!(json["type_url"], "/cosmos.authz.v1beta1.MsgGrant");
+        assert_eq!(json["value"]["grant"]["authorization"]["type_url"], "/cosmos.bank.v1beta1.SendAuthorization");
+    }
+
+    fn tx_of(messages: Vec<StandardCosmosMsg>) -> DecodedTx {
+        DecodedTx { _body: Default::default(), _auth_info: Default::default(), _signatures: vec![], _messages: messages }
+    }
+
+    #[test]
+    fn involved_addresses_cover_bank_and_staking() {
+        let send = MsgSend {
+            from_address: "twilight1alice".to_string(),
+            to_address: "twilight1bob".to_string(),
+            amount: vec![coin("nyks", "5")],
+        };
+        let delegate = MsgDelegate {
+            delegator_address: "twilight1bob".to_string(),
+            validator_address: "twilightvaloper1val".to_string(),
+            amount: Some(coin("nyks", "5")),
+        };
+        let redelegate = MsgBeginRedelegate {
+            delegator_address: "twilight1alice".to_string(),
+            validator_src_address: "twilightvaloper1val".to_string(),
+            validator_dst_address: "twilightvaloper1other".to_string(),
+            amount: None,
+        };
+        let tx = tx_of(vec![
+            StandardCosmosMsg::BankSend(send),
+            StandardCosmosMsg::StakingDelegate(delegate),
+            StandardCosmosMsg::StakingBeginRedelegate(redelegate),
+        ]);
+
+        assert_eq!(
+            tx.involved_addresses(),
+            ["twilight1alice", "twilight1bob", "twilightvaloper1val", "twilightvaloper1other"]
+        );
+    }
+
+    #[test]
+    fn involved_addresses_cover_nyks_and_exec() {
+        let deposit = nyksBridge::MsgConfirmBtcDeposit {
+            reserve_address: "bc1qreserve".to_string(),
+            twilight_deposit_address: "twilight1dep".to_string(),
+            oracle_address: "twilight1oracle".to_string(),
+            ..Default::default()
+        };
+        let exec = StandardCosmosMsg::AuthzExec {
+            grantee: "twilight1grantee".to_string(),
+            msgs: vec![StandardCosmosMsg::NyksConfirmBtcDeposit(deposit)],
+        };
+        let unknown = StandardCosmosMsg::Unknown { type_url: "/example.v1.MsgUnknown".to_string(), raw_value_hex: String::new() };
+
+        // The reserve's BTC address isn't a chain address
+        assert_eq!(
+            tx_of(vec![exec, unknown]).involved_addresses(),
+            ["twilight1grantee", "twilight1dep", "twilight1oracle"]
+        );
+        assert!(tx_of(vec![StandardCosmosMsg::BankSend(Default::default())]).involved_addresses().is_empty());
+    }
+
+    #[test]
+    fn transfer_tx_addresses_link_to_the_stored_qq_tx() {
+        let transfer = |byte_code: &str| {
+            StandardCosmosMsg::NyksZkosMsgTransferTx(nyksZkos::MsgTransferTx {
+                tx_byte_code: byte_code.to_string(),
+                zk_oracle_address: "twilight1oracle".to_string(),
+                ..Default::default()
+            })
+        };
+        let send = MsgSend {
+            from_address: "twilight1alice".to_string(),
+            to_address: "twilightvaloper1notanaccount".to_string(),
+            amount: vec![],
+        };
+        let stored = qq_tx_hash("0xabcd").unwrap();
+        let mut writer = MemoryWriter::default();
+        writer.qq_txs.insert(stored.clone(), "Transfer".to_string());
+
+        let tx = tx_of(vec![StandardCosmosMsg::BankSend(send), transfer("0xabcd"), transfer("0xef01")]);
+        record_address_txs(&mut writer, &tx, 12).unwrap();
+
+        // Only the stored QQ tx is linked, and only to Twilight accounts
+        assert_eq!(
+            writer.address_txs,
+            [("twilight1alice".to_string(), stored.clone()), ("twilight1oracle".to_string(), stored)].into()
+        );
+    }
+
+    #[test]
+    fn exec_to_json_nests_inner_messages() {
+        let msg = StandardCosmosMsg::AuthzExec {
+            grantee: "twilight1grantee".to_string(),
+            msgs: vec![StandardCosmosMsg::DecodeFailed {
+                type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(),
+                error: "bad bytes".to_string(),
+            }],
+        };
+        let json = msg.to_json();
+        assert_eq!(json["type_url"], "/cosmos.authz.v1beta1.MsgExec");
+        assert_eq!(json["value"]["grantee"], "twilight1grantee");
+        assert_eq!(
+            json["value"]["msgs"][0],
+            json!({ "type_url": "/cosmos.bank.v1beta1.MsgSend", "error": "bad bytes" })
+        );
+    }
+
+    #[test]
+    fn multi_send_totals_cover_every_input() {
+        let totals = multi_send_input_totals(&two_input_multi_send("twilight1a", "twilight1b"));
+        assert_eq!(
+            totals,
+            vec![
+                ("twilight1a".to_string(), "nyks".to_string(), 150),
+                ("twilight1b".to_string(), "nyks".to_string(), 7),
+                ("twilight1b".to_string(), "sats".to_string(), 3),
+            ]
+        );
+    }
+
+    #[test]
+    #[ignore = "requires a Postgres instance at DATABASE_URL"]
+    fn multi_send_updates_every_input_address() {
+        crate::db::run_migrations().expect("migrations");
+        let nonce = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let (from_a, from_b) = (format!("twilight1msa{}", nonce), format!("twilight1msb{}", nonce));
+        let any = Any {
+            type_url: "/cosmos.bank.v1beta1.MsgMultiSend".to_string(),
+            value: two_input_multi_send(&from_a, &from_b).encode_to_vec(),
+        };
+
+        let decoded = decode_standard_any_pooled(&any, 42).expect("decode");
+        assert!(matches!(decoded, StandardCosmosMsg::BankMultiSend(_)));
+
+        assert_eq!(get_transaction_count(&from_a).unwrap(), 1);
+        assert_eq!(get_transaction_count(&from_b).unwrap(), 1);
+        assert_eq!(get_funds_moved_total(&from_a).unwrap(), Some(150));
+        assert_eq!(get_funds_moved_total(&from_b).unwrap(), Some(10));
+    }
+
+    #[test]
+    fn multi_send_records_every_input_in_memory() {
+        let any = Any {
+            type_url: "/cosmos.bank.v1beta1.MsgMultiSend".to_string(),
+            value: two_input_multi_send("twilight1a", "twilight1b").encode_to_vec(),
+        };
+        let mut writer = MemoryWriter::default();
+
+        decode_standard_any(&mut writer, &any, 42).expect("decode");
+        decode_standard_any(&mut writer, &any, 42).expect("decode");
+        assert_eq!(writer.transaction_count("twilight1a"), 1, "counted once per block");
+        assert_eq!(writer.transaction_count("twilight1b"), 1);
+        assert_eq!(writer.funds_moved_total("twilight1a"), Some(300));
+        assert_eq!(writer.funds_moved_total("twilight1b"), Some(20));
+        assert_eq!(writer.funds_moved_total("twilight1dest"), None);
+    }
+
+    #[test]
+    #[ignore = "requires a Postgres instance at DATABASE_URL"]
+    fn staking_messages_count_for_the_delegator() {
+        crate::db::run_migrations().expect("migrations");
+        let nonce = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let delegator = format!("twilight1stake{}", nonce);
+
+        let delegate = MsgDelegate {
+            delegator_address: delegator.clone(),
+            validator_address: "twilightvaloper1a".to_string(),
+            amount: Some(coin("nyks", "100")),
+        };
+        let undelegate = MsgUndelegate {
+            delegator_address: delegator.clone(),
+            validator_address: "twilightvaloper1a".to_string(),
+            amount: Some(coin("nyks", "40")),
+        };
+        let redelegate = MsgBeginRedelegate {
+            delegator_address: delegator.clone(),
+            validator_src_address: "twilightvaloper1a".to_string(),
+            validator_dst_address: "twilightvaloper1b".to_string(),
+            amount: Some(coin("nyks", "60")),
+        };
+        let msgs = [
+            ("/cosmos.staking.v1beta1.MsgDelegate", delegate.encode_to_vec()),
+            ("/cosmos.staking.v1beta1.MsgUndelegate", undelegate.encode_to_vec()),
+            ("/cosmos.staking.v1beta1.MsgBeginRedelegate", redelegate.encode_to_vec()),
+        ];
+
+        // One block per message: transaction_count is one row per (address, block)
+        for (i, (type_url, value)) in msgs.into_iter().enumerate() {
+            let any = Any { type_url: type_url.to_string(), value };
+            decode_standard_any_pooled(&any, 100 + i as u64).expect("decode");
+            assert_eq!(get_transaction_count(&delegator).unwrap(), i as i64 + 1);
+        }
+    }
+
+    fn pairs(list: &[(&str, &str)]) -> Vec<(String, String)> {
+        list.iter().map(|(a, b)| (a.to_string(), b.to_string())).collect()
+    }
+
+    #[test]
+    fn transfer_maps_every_output() {
+        let mut writer = MemoryWriter {
+            addr_mappings: pairs(&[("twilight1sender", "qsender"), ("twilight1recipient", "qrecipient")]).into_iter().collect(),
+            ..Default::default()
+        };
+        let recipients = pairs(&[("qrecipient", "acct1"), ("qunknown", "acct2")]);
+
+        assert!(record_transfer(&mut writer, &["qsender".to_string()], "acct0", &recipients, 5).unwrap());
+        assert!(writer.has_mapping("twilight1sender", "acct0"));
+        assert!(writer.has_mapping("twilight1recipient", "acct1"));
+        assert!(!writer.addr_mappings.iter().any(|(_, q)| q == "acct2"));
+        assert_eq!(writer.transactions, [("twilight1sender".to_string(), 5)].into());
+
+        let mut unknown_sender = MemoryWriter::default();
+        assert!(!record_transfer(&mut unknown_sender, &["qsender".to_string()], "acct0", &recipients, 5).unwrap());
+        assert!(unknown_sender.addr_mappings.is_empty() && unknown_sender.transactions.is_empty());
+    }
+
+    fn mint_burn_any(mint_or_burn: bool) -> Any {
+        let msg = nyksZkos::MsgMintBurnTradingBtc {
+            mint_or_burn,
+            btc_value: 1500,
+            qq_account: "0c2e4a".to_string(),
+            encrypt_scalar: String::new(),
+            twilight_address: "twilight1trader".to_string(),
+        };
+        Any { type_url: "/twilightproject.nyks.zkos.MsgMintBurnTradingBtc".to_string(), value: msg.encode_to_vec() }
+    }
+
+    #[test]
+    fn trading_mint_records_dark_mint_and_mapping() {
+        let mut writer = MemoryWriter::default();
+
+        decode_standard_any(&mut writer, &mint_burn_any(true), 7).expect("decode");
+        assert_eq!(writer.dark_minted, [("twilight1trader".to_string(), ("0c2e4a".to_string(), 1500))].into());
+        assert!(writer.dark_burned.is_empty());
+        assert_eq!(writer.addr_mappings, pairs(&[("twilight1trader", "0c2e4a")]).into_iter().collect());
+        assert_eq!(writer.transaction_count("twilight1trader"), 1);
+    }
+
+    #[test]
+    fn trading_burn_records_dark_burn_and_mapping() {
+        let mut writer = MemoryWriter::default();
+
+        decode_standard_any(&mut writer, &mint_burn_any(false), 8).expect("decode");
+        assert_eq!(writer.dark_burned, [("twilight1trader".to_string(), ("0c2e4a".to_string(), 1500))].into());
+        assert!(writer.dark_minted.is_empty());
+        assert_eq!(writer.addr_mappings, pairs(&[("twilight1trader", "0c2e4a")]).into_iter().collect());
+        assert_eq!(writer.transaction_count("twilight1trader"), 1);
+    }
+
+    #[test]
+    fn registered_deposit_address_is_recorded() {
+        let register = nyksBridge::MsgRegisterBtcDepositAddress {
+            btc_deposit_address: "bc1qdeposit".to_string(),
+            btc_satoshi_test_amount: 5_000,
+            twilight_staking_amount: 10_000,
+            twilight_address: "twilight1depositor".to_string(),
+        };
+        let any = Any {
+            type_url: "/twilightproject.nyks.bridge.MsgRegisterBtcDepositAddress".to_string(),
+            value: register.encode_to_vec(),
+        };
+        let mut writer = MemoryWriter::default();
+
+        decode_standard_any(&mut writer, &any, 42).expect("decode");
+
+        assert_eq!(writer.btc_deposit_addresses, [("bc1qdeposit".to_string(), ("twilight1depositor".to_string(), 42))].into());
+    }
+
+    #[test]
+    fn sweep_round_messages_are_recorded_in_order() {
+        let propose = nyksBridge::MsgProposeSweepAddress {
+            btc_address: "bc1qnewreserve".to_string(),
+            btc_script: "0014cc".to_string(),
+            reserve_id: 2,
+            round_id: 7,
+            judge_address: "twilight1judge".to_string(),
+        };
+        let sign = nyksBridge::MsgSignSweep {
+            reserve_id: 2,
+            round_id: 7,
+            signer_public_key: "02ab".to_string(),
+            sweep_signature: vec!["3044".to_string()],
+            signer_address: "twilight1signer".to_string(),
+        };
+        let any = |type_url: &str, value: Vec<u8>| Any { type_url: type_url.to_string(), value };
+        let mut writer = MemoryWriter::default();
+
+        decode_standard_any(&mut writer, &any("/twilightproject.nyks.bridge.MsgProposeSweepAddress", propose.encode_to_vec()), 50)
+            .expect("decode");
+        decode_standard_any(&mut writer, &any("/twilightproject.nyks.bridge.MsgSignSweep", sign.encode_to_vec()), 51)
+            .expect("decode");
+
+        assert_eq!(
+            writer.sweep_events,
+            [
+                (SweepEventKind::ProposeSweepAddress, 2, 7, "twilight1judge".to_string()),
+                (SweepEventKind::SignSweep, 2, 7, "twilight1signer".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reserve_reregistration_keeps_the_first_height() {
+        let register = |judge: &str| Any {
+            type_url: "/twilightproject.nyks.bridge.MsgRegisterReserveAddress".to_string(),
+            value: nyksBridge::MsgRegisterReserveAddress {
+                fragment_id: 4,
+                reserve_script: "0014aa".to_string(),
+                reserve_address: "bc1qreserve".to_string(),
+                judge_address: judge.to_string(),
+            }
+            .encode_to_vec(),
+        };
+        let mut writer = MemoryWriter::default();
+
+        decode_standard_any(&mut writer, &register("twilight1judgeone"), 20).expect("decode");
+        decode_standard_any(&mut writer, &register("twilight1judgetwo"), 30).expect("decode");
+
+        assert_eq!(writer.reserve_addresses, [("bc1qreserve".to_string(), (4, "twilight1judgetwo".to_string(), 20))].into());
+    }
+
+    #[test]
+    fn transient_connection_errors_are_retried() {
+        let send = MsgSend {
+            from_address: "twilight1sender".to_string(),
+            to_address: "twilight1receiver".to_string(),
+            amount: vec![coin("nyks", "5")],
+        };
+        let any = Any { type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(), value: send.encode_to_vec() };
+
+        let mut writer = MemoryWriter { closed_connection_failures: 2, ..Default::default() };
+        retry_on_connection_error("test block", || decode_standard_any(&mut writer, &any, 9).map(|_| ()))
+            .expect("succeeds once the connection is back");
+        assert!(writer.transactions.contains(&("twilight1sender".to_string(), 9)));
+
+        let mut writer = MemoryWriter { closed_connection_failures: 10, ..Default::default() };
+        let err = retry_on_connection_error("test block", || decode_standard_any(&mut writer, &any, 9).map(|_| ()))
+            .expect_err("gives up while the database stays down");
+        assert!(is_connection_error(&err));
+        assert!(writer.transactions.is_empty());
+    }
+
+    #[test]
+    fn withdrawals_are_confirmed_through_their_payout() {
+        use bitcoin::{absolute::LockTime, address::NetworkUnchecked, transaction::Version, Address, Amount, TxIn, TxOut};
+
+        const BTC: &str = "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq";
+        let request = nyksBridge::MsgWithdrawBtcRequest {
+            withdraw_address: BTC.to_string(),
+            reserve_id: 3,
+            withdraw_amount: 20_000,
+            twilight_address: "twilight1withdrawer".to_string(),
+        };
+        let script_pubkey = BTC.parse::<Address<NetworkUnchecked>>().unwrap().assume_checked().script_pubkey();
+        let payout = bitcoin::Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn::default()],
+            output: vec![
+                TxOut { value: Amount::from_sat(20_000), script_pubkey: script_pubkey.clone() },
+                TxOut { value: Amount::from_sat(5_000), script_pubkey },
+            ],
+        };
+        let txid = payout.compute_txid().to_string();
+        let final_tx = nyksBridge::MsgWithdrawTxFinal {
+            creator: "twilight1judge".to_string(),
+            judge_address: "twilight1judge".to_string(),
+            btc_tx: bitcoin::consensus::encode::serialize_hex(&payout),
+        };
+        let confirm = nyksBridge::MsgConfirmBtcWithdraw {
+            tx_hash: txid.clone(),
+            height: 840_100,
+            hash: "00ff".to_string(),
+            judge_address: "twilight1judge".to_string(),
+        };
+        let mut writer = MemoryWriter::default();
+
+        let any = |type_url: &str, value: Vec<u8>| Any { type_url: type_url.to_string(), value };
+        decode_standard_any(&mut writer, &any("/twilightproject.nyks.bridge.MsgWithdrawBtcRequest", request.encode_to_vec()), 77)
+            .expect("decode");
+        decode_standard_any(&mut writer, &any("/twilightproject.nyks.bridge.MsgWithdrawTxFinal", final_tx.encode_to_vec()), 85)
+            .expect("decode");
+
+        let key = ("twilight1withdrawer".to_string(), BTC.to_string(), 3, 77);
+        assert_eq!(writer.btc_withdrawals, [(key.clone(), 20_000)].into());
+        assert_eq!(writer.lit_burned, [("twilight1withdrawer".to_string(), 20_000)].into());
+        assert_eq!(writer.btc_withdrawal_payouts, [(key.clone(), txid.clone())].into(), "only the matching output pays it");
+        assert!(writer.btc_withdrawals_confirmed.is_empty(), "still pending until a judge confirms");
+
+        decode_standard_any(&mut writer, &any("/twilightproject.nyks.bridge.MsgConfirmBtcWithdraw", confirm.encode_to_vec()), 90)
+            .expect("decode");
+
+        assert_eq!(writer.btc_withdraw_confirmations, [(txid, (840_100, "twilight1judge".to_string()))].into());
+        assert_eq!(writer.btc_withdrawals_confirmed, [key].into());
+    }
+
+    #[test]
+    fn unparseable_withdrawal_transactions_link_nothing() {
+        let final_tx = nyksBridge::MsgWithdrawTxFinal {
+            creator: "twilight1judge".to_string(),
+            judge_address: "twilight1judge".to_string(),
+            btc_tx: "not a transaction".to_string(),
+        };
+        let any = Any { type_url: "/twilightproject.nyks.bridge.MsgWithdrawTxFinal".to_string(), value: final_tx.encode_to_vec() };
+        let mut writer = MemoryWriter::default();
+
+        assert!(matches!(decode_standard_any(&mut writer, &any, 85), Ok(StandardCosmosMsg::NyksWithdrawTxFinal(_))));
+        assert!(writer.btc_withdrawal_payouts.is_empty());
+    }
+
+    #[test]
+    fn unusable_send_amounts_are_skipped() {
+        let send = MsgSend {
+            from_address: "twilight1from".to_string(),
+            to_address: "twilight1to".to_string(),
+            amount: vec![coin("nyks", "99999999999999999999999999"), coin("sats", "lots"), coin("nyks", "25")],
+        };
+        let any = Any { type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(), value: send.encode_to_vec() };
+        let mut writer = MemoryWriter::default();
+
+        decode_standard_any(&mut writer, &any, 3).expect("decode");
+        assert_eq!(writer.funds_moved, [(("twilight1to".to_string(), "nyks".to_string()), 25)].into());
+    }
+
+    #[test]
+    fn unusable_ibc_transfer_amount_is_skipped() {
+        let msg = MsgTransfer {
+            source_port: "transfer".to_string(),
+            source_channel: "channel-0".to_string(),
+            token: Some(IbcCoin { denom: "nyks".to_string(), amount: "99999999999999999999999999".to_string() }),
+            sender: "twilight1ibcsender".to_string(),
+            receiver: "osmo1receiver".to_string(),
+            timeout_height: None,
+            timeout_timestamp: 1_700_000_000_000_000_000,
+            memo: String::new(),
+        };
+        let any = Any { type_url: "/ibc.applications.transfer.v1.MsgTransfer".to_string(), value: msg.encode_to_vec() };
+        let mut writer = MemoryWriter::default();
+
+        assert!(matches!(decode_standard_any(&mut writer, &any, 3), Ok(StandardCosmosMsg::IbcTransfer(_))));
+        assert_eq!(writer.transaction_count("twilight1ibcsender"), 1);
+        assert!(writer.funds_moved.is_empty());
+        assert!(writer.ibc_transfers.is_empty());
+    }
+
+    #[test]
+    fn sats_amounts_past_i64_are_not_recorded() {
+        let deposit = nyksBridge::MsgConfirmBtcDeposit {
+            twilight_deposit_address: "twilight1depositor".to_string(),
+            deposit_amount: u64::MAX,
+            ..Default::default()
+        };
+        let withdraw = nyksBridge::MsgWithdrawBtcRequest {
+            withdraw_address: "bc1qwithdraw".to_string(),
+            reserve_id: 3,
+            withdraw_amount: u64::MAX,
+            twilight_address: "twilight1withdrawer".to_string(),
+        };
+        let mint = nyksZkos::MsgMintBurnTradingBtc {
+            mint_or_burn: true,
+            btc_value: u64::MAX,
+            qq_account: "0c2e4a".to_string(),
+            encrypt_scalar: String::new(),
+            twilight_address: "twilight1trader".to_string(),
+        };
+        let any = |type_url: &str, value: Vec<u8>| Any { type_url: type_url.to_string(), value };
+        let mut writer = MemoryWriter::default();
+
+        for msg in [
+            any("/twilightproject.nyks.bridge.MsgConfirmBtcDeposit", deposit.encode_to_vec()),
+            any("/twilightproject.nyks.bridge.MsgWithdrawBtcRequest", withdraw.encode_to_vec()),
+            any("/twilightproject.nyks.zkos.MsgMintBurnTradingBtc", mint.encode_to_vec()),
+        ] {
+            decode_standard_any(&mut writer, &msg, 4).expect("decode");
+        }
+        assert!(writer.lit_minted.is_empty());
+        assert!(writer.lit_burned.is_empty());
+        assert!(writer.btc_withdrawals.is_empty());
+        assert!(writer.dark_minted.is_empty() && writer.dark_burned.is_empty());
+        assert!(writer.addr_mappings.is_empty() && writer.transactions.is_empty());
+        assert_eq!(sats_amount(i64::MAX as u64, "test"), Some(i64::MAX));
+    }
+
+    #[test]
+    fn coin_amounts_must_fit_i64() {
+        assert_eq!(parse_coin_amount(&coin("nyks", "9223372036854775807"), "test"), Some(i64::MAX));
+        assert_eq!(parse_coin_amount(&coin("nyks", "9223372036854775808"), "test"), None);
+        assert_eq!(parse_coin_amount(&coin("nyks", "12abc"), "test"), None);
+        assert_eq!(parse_coin_amount(&coin("nyks", ""), "test"), None);
+    }
+
+    #[test]
+    fn unconvertible_output_has_no_qq_account() {
+        assert_eq!(qq_account_hex(Err::<u8, _>("Invalid Output Type")), None);
+        assert_eq!(qq_account_hex(Ok::<_, &str>(0x0cu8)), Some("0c".to_string()));
+    }
+
+    #[test]
+    fn fee_payer_prefers_granter_then_payer_then_signer() {
+        let mut fee = Fee {
+            amount: vec![],
+            gas_limit: 200_000,
+            payer: String::new(),
+            granter: String::new(),
+        };
+        assert_eq!(fee_payer(&fee, Some("twilight1signer")), Some("twilight1signer".to_string()));
+        assert_eq!(fee_payer(&fee, None), None);
+
+        fee.payer = "twilight1payer".to_string();
+        assert_eq!(fee_payer(&fee, Some("twilight1signer")), Some("twilight1payer".to_string()));
+
+        fee.granter = "twilight1granter".to_string();
+        assert_eq!(fee_payer(&fee, Some("twilight1signer")), Some("twilight1granter".to_string()));
+    }
+}